@@ -0,0 +1,286 @@
+use std::{
+    alloc::{
+        GlobalAlloc,
+        Layout,
+        System,
+    },
+    sync::atomic::{
+        AtomicUsize,
+        Ordering,
+    },
+};
+
+use criterion::{
+    criterion_group,
+    criterion_main,
+    BatchSize,
+    BenchmarkId,
+    Criterion,
+    Throughput,
+};
+
+use into_the_breach::{
+    Fire,
+    Grid,
+    Particle,
+    Point,
+    Rect,
+    Sand,
+    Storage,
+    Wood,
+};
+
+/// Heap allocations made so far, counted by [`Counting`].
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// The system allocator, counting every allocation it makes so the
+/// benchmarks can report how many an update takes.
+struct Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+const WIDTH: usize = 384;
+const HEIGHT: usize = 204;
+
+/// Grid sizes compared by the throughput benchmarks, the middle one being
+/// the window's default.
+const SIZES: [(usize, usize); 3] = [(128, 68), (WIDTH, HEIGHT), (768, 408)];
+
+/// A grid whose top half is loose sand, all of it about to fall.
+fn busy() -> Grid {
+    half_full(WIDTH, HEIGHT)
+}
+
+fn half_full(width: usize, height: usize) -> Grid {
+    let mut grid = Grid::new(width, height);
+    for y in 0 .. height / 2 {
+        for x in 0 .. width {
+            grid[Point::new(x as i32, y as i32)] = Some(Particle::Sand(Sand));
+        }
+    }
+    grid
+}
+
+/// A grid packed with sand that has already come to rest.
+fn settled(width: usize, height: usize) -> Grid {
+    let mut grid = Grid::new(width, height);
+    for y in 0 .. height {
+        for x in 0 .. width {
+            grid[Point::new(x as i32, y as i32)] = Some(Particle::Sand(Sand));
+        }
+    }
+    grid.update();
+    grid
+}
+
+/// A grid whose bottom half is a settled block of sand, with one grain
+/// falling from the top.
+fn settled_pile() -> Grid {
+    large_settled_pile(WIDTH, HEIGHT)
+}
+
+fn large_settled_pile(width: usize, height: usize) -> Grid {
+    let mut grid = Grid::new(width, height);
+    for y in height / 2 .. height {
+        for x in 0 .. width {
+            grid[Point::new(x as i32, y as i32)] = Some(Particle::Sand(Sand));
+        }
+    }
+    grid.update();
+
+    grid[Point::new(width as i32 / 2, 0)] = Some(Particle::Sand(Sand));
+    grid
+}
+
+fn settled_pile_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("settled pile");
+
+    group.bench_function("naive", |b| {
+        let mut grid = settled_pile();
+        b.iter(|| {
+            grid.wake_all();
+            grid.update();
+        });
+    });
+
+    group.bench_function("active set", |b| {
+        let mut grid = settled_pile();
+        b.iter(|| grid.update());
+    });
+
+    group.finish();
+}
+
+fn busy_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("busy grid");
+
+    group.bench_function("serial", |b| {
+        b.iter_batched(busy, |mut grid| grid.update(), BatchSize::LargeInput);
+    });
+
+    group.bench_function("parallel", |b| {
+        b.iter_batched(busy, |mut grid| grid.update_parallel(), BatchSize::LargeInput);
+    });
+
+    group.finish();
+}
+
+/// Cells updated per second for grids of each size in `SIZES`, from empty,
+/// through half full and falling, to completely settled.
+fn throughput(c: &mut Criterion) {
+    let mut group = c.benchmark_group("throughput");
+
+    for &(width, height) in &SIZES {
+        let size = format!("{}x{}", width, height);
+        group.throughput(Throughput::Elements((width * height) as u64));
+
+        group.bench_with_input(BenchmarkId::new("empty", &size), &(width, height), |b, &(w, h)| {
+            let mut grid = Grid::new(w, h);
+            b.iter(|| grid.update());
+        });
+
+        group.bench_with_input(BenchmarkId::new("half full", &size), &(width, height), |b, &(w, h)| {
+            b.iter_batched(|| half_full(w, h), |mut grid| grid.update(), BatchSize::LargeInput);
+        });
+
+        group.bench_with_input(BenchmarkId::new("settled", &size), &(width, height), |b, &(w, h)| {
+            let mut grid = settled(w, h);
+            b.iter(|| grid.update());
+        });
+    }
+
+    group.finish();
+}
+
+/// A large grid with only a small region moving, updated cell by cell,
+/// chunk by chunk, and in full.
+fn chunked_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("small active region");
+    let pile = || large_settled_pile(1024, 1024);
+
+    group.bench_function("naive", |b| {
+        let mut grid = pile();
+        b.iter(|| {
+            grid.wake_all();
+            grid.update();
+        });
+    });
+
+    group.bench_function("active set", |b| {
+        let mut grid = pile();
+        b.iter(|| grid.update());
+    });
+
+    group.bench_function("chunks", |b| {
+        let mut grid = pile();
+        b.iter(|| grid.update_chunked());
+    });
+
+    group.finish();
+}
+
+/// Side of the huge grid the storage benchmarks compare on.
+const HUGE: usize = 4000;
+
+/// A huge grid in the given storage, one percent full: a band of sand
+/// across the top, all of it about to fall.
+fn sparse_band(storage: Storage) -> Grid {
+    let mut grid = Grid::with_storage(HUGE, HUGE, 0, storage);
+    grid.fill_rect(Rect::new(0, 0, HUGE as u32, HUGE as u32 / 100), &Sand);
+    grid
+}
+
+/// Memory taken by a mostly empty huge grid, its cells and in all, and the
+/// time to update it, with dense and sparse storage.
+fn sparse_storage(c: &mut Criterion) {
+    let mut group = c.benchmark_group("1% full 4000x4000");
+    group.sample_size(10);
+
+    for (name, storage) in [("dense", Storage::Dense), ("sparse", Storage::Sparse)] {
+        let grid = sparse_band(storage);
+        println!(
+            "{} storage: {} MiB of cells, {} MiB in all",
+            name, grid.storage_bytes() >> 20, grid.memory_bytes() >> 20,
+        );
+        drop(grid);
+
+        group.bench_function(name, |b| {
+            // Handing the grid back leaves freeing it out of the timing.
+            let update = |mut grid: Grid| {
+                grid.update();
+                grid
+            };
+            b.iter_batched(|| sparse_band(storage), update, BatchSize::LargeInput);
+        });
+    }
+
+    group.finish();
+}
+
+/// A grid whose bottom half is wood, alight all along its top.
+fn burning() -> Grid {
+    let (width, height) = (WIDTH as u32, HEIGHT as u32);
+    let mut grid = Grid::new(WIDTH, HEIGHT);
+    grid.fill_rect(Rect::new(0, height as i32 / 2, width, height / 2), &Wood);
+    grid.fill_rect(Rect::new(0, height as i32 / 2 - 1, width, 1), &Fire::new());
+    grid
+}
+
+/// Heap allocations per update over `ticks` updates of `grid`.
+fn allocations_per_update(grid: &mut Grid, ticks: usize) -> f64 {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    grid.run(ticks);
+    (ALLOCATIONS.load(Ordering::Relaxed) - before) as f64 / ticks as f64
+}
+
+/// How many heap allocations an update makes. Particles live in their cells,
+/// so neither moving sand nor the flames and smoke of burning wood allocate;
+/// what's left is the grid's working buffers.
+fn allocations(c: &mut Criterion) {
+    let mut group = c.benchmark_group("allocations");
+
+    for (name, scene) in [("falling sand", busy as fn() -> Grid), ("burning wood", burning)] {
+        let mut grid = scene();
+        // The first update sizes the grid's working buffers.
+        grid.update();
+        println!("{}: {:.1} allocations per update", name, allocations_per_update(&mut grid, 20));
+
+        group.bench_function(name, |b| {
+            let update = |mut grid: Grid| {
+                grid.update();
+                grid
+            };
+            b.iter_batched(scene, update, BatchSize::LargeInput);
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    settled_pile_update,
+    busy_update,
+    throughput,
+    chunked_update,
+    sparse_storage,
+    allocations,
+);
+criterion_main!(benches);
@@ -0,0 +1,190 @@
+use sdl2::rect::Rect;
+
+use into_the_breach::Point;
+
+const MAX_ZOOM: u32 = 16;
+
+/// Which part of the grid is on screen, and how large. Zoom is a whole
+/// multiple of the pixel size so every cell stays the same size. A grid
+/// narrower or shorter than the window is centred in it, leaving a border of
+/// background round the edges.
+pub struct Camera {
+    pixel_size: u32,
+    zoom: u32,
+    /// The grid cell drawn in the top-left corner of the window.
+    offset: Point,
+    /// Window size in pixels.
+    viewport: (u32, u32),
+    /// Grid size in cells.
+    bounds: (usize, usize),
+}
+
+impl Camera {
+    pub fn new(pixel_size: u32, viewport: (u32, u32), bounds: (usize, usize)) -> Camera {
+        Camera {
+            pixel_size,
+            zoom: 1,
+            offset: Point::new(0, 0),
+            viewport,
+            bounds,
+        }
+    }
+
+    pub fn cell_size(&self) -> u32 {
+        self.pixel_size * self.zoom
+    }
+
+    /// Where the cell at `point` is drawn.
+    pub fn cell_rect(&self, point: Point) -> Rect {
+        let size = self.cell_size();
+        let (left, top) = self.margin();
+        Rect::new(
+            left + (point.x - self.offset.x) * size as i32,
+            top + (point.y - self.offset.y) * size as i32,
+            size,
+            size,
+        )
+    }
+
+    /// The cell under a position in the window.
+    pub fn cell_at(&self, x: i32, y: i32) -> Point {
+        let size = self.cell_size() as i32;
+        let (left, top) = self.margin();
+        Point::new(
+            self.offset.x + (x - left).div_euclid(size),
+            self.offset.y + (y - top).div_euclid(size),
+        )
+    }
+
+    /// Pixels between the window's top-left corner and the grid's, where
+    /// the grid doesn't fill the window.
+    fn margin(&self) -> (i32, i32) {
+        let size = self.cell_size() as i64;
+        let spare = |pixels: u32, cells: usize| {
+            ((pixels as i64 - cells as i64 * size).max(0) / 2) as i32
+        };
+        (spare(self.viewport.0, self.bounds.0), spare(self.viewport.1, self.bounds.1))
+    }
+
+    /// The first visible cell, and one past the last, along each axis.
+    /// Cells only partly in view at the far edges count as visible.
+    pub fn visible(&self) -> (Point, Point) {
+        let size = self.cell_size();
+        let across = |pixels: u32| pixels.div_ceil(size) as i32;
+        let end = Point::new(
+            (self.offset.x + across(self.viewport.0)).min(self.bounds.0 as i32),
+            (self.offset.y + across(self.viewport.1)).min(self.bounds.1 as i32),
+        );
+
+        (self.offset, end)
+    }
+
+    pub fn is_visible(&self, point: Point) -> bool {
+        let (start, end) = self.visible();
+        (start.x .. end.x).contains(&point.x) && (start.y .. end.y).contains(&point.y)
+    }
+
+    /// Zooms in by `steps`, or out if negative, keeping the cell under the
+    /// window position `anchor` where it is. Returns whether anything
+    /// changed.
+    pub fn zoom_by(&mut self, steps: i32, anchor: (i32, i32)) -> bool {
+        let zoom = (self.zoom as i32 + steps).clamp(1, MAX_ZOOM as i32) as u32;
+        if zoom == self.zoom { return false; }
+
+        let anchored = self.cell_at(anchor.0, anchor.1);
+        self.zoom = zoom;
+        let size = self.cell_size() as i32;
+        let (left, top) = self.margin();
+        self.offset = Point::new(
+            anchored.x - (anchor.0 - left) / size,
+            anchored.y - (anchor.1 - top) / size,
+        );
+        self.clamp();
+
+        true
+    }
+
+    /// Moves the view by a number of cells.
+    pub fn pan(&mut self, dx: i32, dy: i32) {
+        self.pan_to(self.offset.offset(dx, dy));
+    }
+
+    /// Moves the view so that `offset` is the top-left cell.
+    pub fn pan_to(&mut self, offset: Point) {
+        self.offset = offset;
+        self.clamp();
+    }
+
+    pub fn offset(&self) -> Point {
+        self.offset
+    }
+
+    /// Keeps the view from scrolling past the edges of the grid.
+    fn clamp(&mut self) {
+        let size = self.cell_size();
+        let max_x = self.bounds.0 as i32 - (self.viewport.0 / size) as i32;
+        let max_y = self.bounds.1 as i32 - (self.viewport.1 / size) as i32;
+
+        self.offset = Point::new(
+            self.offset.x.clamp(0, max_x.max(0)),
+            self.offset.y.clamp(0, max_y.max(0)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera() -> Camera {
+        Camera::new(5, (100, 50), (20, 10))
+    }
+
+    #[test]
+    fn maps_window_positions_to_cells_and_back() {
+        let mut camera = camera();
+        camera.zoom_by(1, (0, 0));
+        camera.pan(3, 2);
+
+        let cell = camera.cell_at(47, 22);
+        assert_eq!(cell, Point::new(7, 4));
+        assert!(camera.cell_rect(cell).contains_point((47, 22)));
+    }
+
+    #[test]
+    fn zooming_keeps_the_anchor_in_place() {
+        let mut camera = camera();
+        let anchor = (60, 30);
+        let before = camera.cell_at(anchor.0, anchor.1);
+
+        assert!(camera.zoom_by(1, anchor));
+
+        assert_eq!(camera.cell_at(anchor.0, anchor.1), before);
+        assert_eq!(camera.visible(), (Point::new(6, 3), Point::new(16, 8)));
+    }
+
+    #[test]
+    fn centres_a_grid_that_does_not_fill_the_window() {
+        // Three pixels spare across and two down, from a window the pixel
+        // size doesn't divide.
+        let camera = Camera::new(5, (103, 52), (20, 10));
+
+        assert_eq!(camera.cell_rect(Point::new(0, 0)), Rect::new(1, 1, 5, 5));
+        assert_eq!(camera.cell_at(1, 1), Point::new(0, 0));
+        assert_eq!(camera.cell_at(100, 50), Point::new(19, 9));
+        // The border belongs to no cell on the grid.
+        assert_eq!(camera.cell_at(0, 0), Point::new(-1, -1));
+        assert_eq!(camera.cell_at(101, 51), Point::new(20, 10));
+    }
+
+    #[test]
+    fn cannot_pan_past_the_grid() {
+        let mut camera = camera();
+        camera.pan(5, 5);
+        assert_eq!(camera.offset(), Point::new(0, 0));
+
+        camera.zoom_by(1, (0, 0));
+        camera.pan(100, -100);
+        assert_eq!(camera.offset(), Point::new(10, 0));
+    }
+}
@@ -0,0 +1,77 @@
+/// A cell coordinate on the grid. Unlike SDL's `Point` this carries no
+/// rendering baggage, so the simulation can run headless.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub const fn new(x: i32, y: i32) -> Point {
+        Point { x, y }
+    }
+
+    pub const fn offset(self, dx: i32, dy: i32) -> Point {
+        Point::new(self.x + dx, self.y + dy)
+    }
+}
+
+/// An opaque RGB color, converted to the renderer's own type at draw time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color { r, g, b }
+    }
+
+    /// Brightens (or, for negative `offset`, darkens) every channel equally,
+    /// saturating at the ends of the range.
+    pub fn shaded(self, offset: i16) -> Color {
+        let channel = |value: u8| (value as i16 + offset).clamp(0, u8::MAX as i16) as u8;
+        Color::rgb(channel(self.r), channel(self.g), channel(self.b))
+    }
+
+    /// Mixes in `amount` of `other`, from `0.0` leaving this color as it is
+    /// to `1.0` replacing it entirely.
+    pub fn mixed(self, other: Color, amount: f32) -> Color {
+        let channel = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * amount).round() as u8;
+        Color::rgb(channel(self.r, other.r), channel(self.g, other.g), channel(self.b, other.b))
+    }
+}
+
+/// A rectangle of cells, `width` across and `height` down from its top-left
+/// cell at `x`, `y`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub const fn new(x: i32, y: i32, width: u32, height: u32) -> Rect {
+        Rect { x, y, width, height }
+    }
+
+    /// The smallest rectangle holding both `a` and `b`, given in any order.
+    pub fn from_corners(a: Point, b: Point) -> Rect {
+        Rect::new(
+            a.x.min(b.x),
+            a.y.min(b.y),
+            a.x.abs_diff(b.x) + 1,
+            a.y.abs_diff(b.y) + 1,
+        )
+    }
+
+    /// Every cell in the rectangle, row by row.
+    pub fn points(self) -> impl Iterator<Item = Point> {
+        (self.y .. self.y + self.height as i32)
+            .flat_map(move |y| (self.x .. self.x + self.width as i32).map(move |x| Point::new(x, y)))
+    }
+}
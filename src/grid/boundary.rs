@@ -0,0 +1,108 @@
+use crate::{
+    Grid,
+    Point,
+};
+
+/// What happens to particles that move off the edge of the grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoundaryMode {
+    /// The edges are walls that everything piles up against.
+    Solid,
+    /// Particles leaving one side come back in on the opposite side.
+    Wrap,
+    /// Particles leaving the grid are gone, as though it had no walls.
+    Void,
+}
+
+impl Grid {
+    pub fn boundary(&self) -> BoundaryMode {
+        self.boundary
+    }
+
+    /// Changes what the edges do. Moves and the cells they wake follow the
+    /// boundary; heat and charge stop at the edges whatever it is.
+    /// Everything is woken, since particles along the edges may now be free
+    /// to move.
+    pub fn set_boundary(&mut self, boundary: BoundaryMode) {
+        self.boundary = boundary;
+        self.wake_all();
+    }
+
+    /// Where a particle of the given density heading for `point` ends up, if
+    /// it can move there at all: `point` itself if that is on the grid,
+    /// otherwise its counterpart across the grid under
+    /// [`BoundaryMode::Wrap`], or `point` itself, off the grid, under
+    /// [`BoundaryMode::Void`], where the particle is removed.
+    pub fn destination(&self, point: Point, density: f32) -> Option<Point> {
+        let point = self.wrapped(point);
+        if self.in_bounds(point) {
+            Some(point).filter(|&point| self.is_passable(point, density))
+        } else {
+            Some(point).filter(|_| self.boundary == BoundaryMode::Void)
+        }
+    }
+
+    /// `point` brought back onto the grid across the edges under
+    /// [`BoundaryMode::Wrap`], and left as it is otherwise.
+    pub(super) fn wrapped(&self, point: Point) -> Point {
+        if self.boundary != BoundaryMode::Wrap { return point; }
+
+        Point::new(
+            point.x.rem_euclid(self.width as i32),
+            point.y.rem_euclid(self.height as i32),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Material,
+        Particle,
+        Sand,
+    };
+
+    /// A 1x3 grid with a grain of sand resting on the bottom edge.
+    fn grain_on_the_floor(boundary: BoundaryMode) -> Grid {
+        let mut grid = Grid::new(1, 3);
+        grid.set_boundary(boundary);
+        grid[Point::new(0, 2)] = Some(Particle::Sand(Sand));
+        grid
+    }
+
+    #[test]
+    fn solid_edges_hold_particles() {
+        let mut grid = grain_on_the_floor(BoundaryMode::Solid);
+
+        grid.update();
+
+        assert!(grid[Point::new(0, 2)].is_some());
+        assert_eq!(grid.count_filled(), 1);
+    }
+
+    #[test]
+    fn wrapping_edges_bring_particles_back_on_the_far_side() {
+        let mut grid = grain_on_the_floor(BoundaryMode::Wrap);
+
+        grid.update();
+
+        assert!(grid[Point::new(0, 0)].is_some());
+        assert_eq!(grid.count_filled(), 1);
+
+        // Sideways too.
+        let mut grid = Grid::new(3, 1);
+        grid.set_boundary(BoundaryMode::Wrap);
+        assert_eq!(grid.destination(Point::new(-1, 0), Sand.density()), Some(Point::new(2, 0)));
+    }
+
+    #[test]
+    fn void_edges_remove_particles() {
+        let mut grid = grain_on_the_floor(BoundaryMode::Void);
+
+        grid.update();
+
+        assert_eq!(grid.count_filled(), 0);
+        assert!(grid.dirty().contains(&Point::new(0, 2)));
+    }
+}
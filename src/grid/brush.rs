@@ -0,0 +1,133 @@
+use rand::Rng;
+
+use crate::{
+    Grid,
+    Material,
+    Point,
+    Rect,
+};
+
+use super::circle;
+
+/// Share of its disc the spray brush puts particles in, before the
+/// material's own [`Material::spawn_density`].
+const SPRAY_DENSITY: f32 = 0.1;
+
+/// The shape of cells the brush paints around the cursor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BrushShape {
+    /// Every cell within the radius.
+    Circle,
+    /// Every cell of the square the radius spans either way, for building
+    /// walls with straight edges.
+    Square,
+    /// A scattering of cells within the radius, a few at a time, for
+    /// sprinkling loose materials.
+    Spray,
+}
+
+impl BrushShape {
+    /// The shape after this one, going round them all in turn.
+    pub fn next(self) -> BrushShape {
+        match self {
+            BrushShape::Circle => BrushShape::Square,
+            BrushShape::Square => BrushShape::Spray,
+            BrushShape::Spray => BrushShape::Circle,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BrushShape::Circle => "circle",
+            BrushShape::Square => "square",
+            BrushShape::Spray => "spray",
+        }
+    }
+
+    /// Every point the brush can paint around `center`, regardless of grid
+    /// bounds.
+    pub(crate) fn area(self, center: Point, radius: i32) -> Box<dyn Iterator<Item = Point>> {
+        match self {
+            BrushShape::Circle | BrushShape::Spray => Box::new(circle(center, radius)),
+            BrushShape::Square => {
+                let side = 2 * radius as u32 + 1;
+                Box::new(Rect::new(center.x - radius, center.y - radius, side, side).points())
+            },
+        }
+    }
+
+    /// Chance that the brush fills each cell of its area with `material`.
+    fn density(self, material: &dyn Material) -> f32 {
+        match self {
+            BrushShape::Circle | BrushShape::Square => material.spawn_density(),
+            BrushShape::Spray => SPRAY_DENSITY * material.spawn_density(),
+        }
+    }
+}
+
+impl Grid {
+    /// Fills in-bounds cells of the brush `shape` of `radius` around
+    /// `center` with copies of `material`, each with a chance of its
+    /// [`Material::spawn_density`], thinned out further for a spray.
+    pub fn paint(&mut self, center: Point, radius: i32, shape: BrushShape, material: &dyn Material) {
+        let density = shape.density(material);
+        for point in shape.area(center, radius) {
+            if density < 1.0 && self.rng.gen::<f32>() >= density { continue; }
+
+            if let Some(idx) = self.index_of(point) {
+                self.place(idx, material.particle());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Stone;
+
+    /// How many cells a brush of `shape` fills painting stone at radius 4
+    /// in the middle of an empty grid.
+    fn painted(shape: BrushShape) -> usize {
+        let mut grid = Grid::new(20, 20);
+        grid.paint(Point::new(10, 10), 4, shape, &Stone);
+        grid.count_filled()
+    }
+
+    #[test]
+    fn circles_fill_the_disc() {
+        assert_eq!(painted(BrushShape::Circle), 49);
+    }
+
+    #[test]
+    fn squares_fill_their_corners_too() {
+        assert_eq!(painted(BrushShape::Square), 81);
+
+        let mut grid = Grid::new(5, 5);
+        grid.paint(Point::new(2, 2), 2, BrushShape::Square, &Stone);
+        assert!(grid[Point::new(0, 0)].is_some());
+        assert!(grid[Point::new(4, 4)].is_some());
+    }
+
+    #[test]
+    fn sprays_scatter_a_few_cells_of_the_disc() {
+        let mut grid = Grid::new(60, 60);
+        grid.paint(Point::new(30, 30), 25, BrushShape::Spray, &Stone);
+
+        // A tenth of the disc's 1961 cells.
+        let count = grid.count_filled();
+        assert!(count > 150 && count < 250, "{} cells", count);
+        assert!(grid.iter_filled().all(|(point, _)| {
+            let (dx, dy) = (point.x - 30, point.y - 30);
+            dx * dx + dy * dy <= 25 * 25
+        }));
+    }
+
+    #[test]
+    fn cycles_through_every_shape() {
+        let shapes: Vec<_> = std::iter::successors(Some(BrushShape::Circle), |shape| Some(shape.next()))
+            .take(4)
+            .collect();
+        assert_eq!(shapes, [BrushShape::Circle, BrushShape::Square, BrushShape::Spray, BrushShape::Circle]);
+    }
+}
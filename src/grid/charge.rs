@@ -0,0 +1,188 @@
+use std::{
+    cmp::Reverse,
+    collections::{
+        HashSet,
+        VecDeque,
+    },
+    mem,
+};
+
+use crate::{
+    Grid,
+    Point,
+};
+
+use super::NEIGHBOURS;
+
+/// Charge given to a cell by [`Grid::electrify`], which fades by one a
+/// tick. Conductors connected to the cell hold the same charge however far
+/// along they are, so this is how long a circuit stays live once nothing
+/// feeds it.
+pub(super) const FULL_CHARGE: u8 = 64;
+
+impl Grid {
+    /// How much electric charge the cell at `point` holds, from `0` up to
+    /// full, or `0` off the grid.
+    pub fn charge_at(&self, point: Point) -> u8 {
+        self.index_of(point).map_or(0, |idx| self.charge[idx])
+    }
+
+    /// Fully charges the cell at `point` if it
+    /// [conducts](crate::Material::conducts). The cell feeds the conductors
+    /// connected to it at its own charge, reaching a cell further each tick,
+    /// and fades unless electrified again, so a circuit stays live only
+    /// while something keeps feeding it.
+    pub fn electrify(&mut self, point: Point) {
+        let idx = match self.index_of(point) {
+            Some(idx) => idx,
+            None => return,
+        };
+        if self.conducts(idx) {
+            self.electrified[idx] = true;
+            self.set_charge(idx, FULL_CHARGE);
+        }
+    }
+
+    /// Lets every charged cell fade by one, and has each electrified cell
+    /// feed the conductors it is connected to: every charged one at its own
+    /// charge, and the uncharged ones next to them, so charge reaches a cell
+    /// further each tick. Only charged cells and their neighbours can
+    /// change, so nothing else is visited.
+    pub(super) fn conduct(&mut self) {
+        let mut charged = self.charged.take_sorted();
+        let mut updates = Vec::new();
+
+        // Every new charge is worked out from the old ones before any are
+        // written, as with heat.
+        for &idx in &charged {
+            let faded = if self.conducts(idx) { self.charge[idx].saturating_sub(1) } else { 0 };
+            updates.push((idx, faded));
+        }
+
+        // The strongest source feeds whatever it reaches first. Fed cells
+        // don't feed anything themselves, so once the sources fade the whole
+        // circuit does.
+        let mut sources: Vec<_> = charged.iter()
+            .copied()
+            .filter(|&idx| self.electrified[idx] && self.conducts(idx))
+            .collect();
+        sources.sort_by_key(|&idx| Reverse(self.charge[idx]));
+        let mut reached = HashSet::new();
+        let mut queue = VecDeque::new();
+        for source in sources {
+            if !reached.insert(source) { continue; }
+
+            let charge = self.charge[source];
+            queue.push_back(source);
+            while let Some(idx) = queue.pop_front() {
+                let position = self.point_of(idx);
+                for &(dx, dy) in &NEIGHBOURS {
+                    let neighbour = match self.index_of(position.offset(dx, dy)) {
+                        Some(neighbour) if self.conducts(neighbour) => neighbour,
+                        _ => continue,
+                    };
+                    if !reached.insert(neighbour) { continue; }
+
+                    if !self.electrified[neighbour] {
+                        updates.push((neighbour, charge));
+                    }
+                    if self.charge[neighbour] > 0 {
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
+        }
+
+        // A cell listed more than once takes the strongest charge offered.
+        updates.sort_unstable_by_key(|&(idx, charge)| (idx, u8::MAX - charge));
+        updates.dedup_by_key(|&mut (idx, _)| idx);
+        for (idx, charge) in updates {
+            self.set_charge(idx, charge);
+        }
+
+        charged.clear();
+        self.charged.recycle(charged);
+    }
+
+    /// Sets the charge of the cell at `idx`, keeping it in the charged set
+    /// while it has any and repainting it when it changes. A cell left with
+    /// none is no longer electrified.
+    fn set_charge(&mut self, idx: usize, charge: u8) {
+        let point = self.point_of(idx);
+        if mem::replace(&mut self.charge[idx], charge) != charge {
+            self.dirty.mark(idx, point);
+        }
+        if charge > 0 {
+            self.charged.mark(idx, point);
+        } else {
+            self.electrified[idx] = false;
+        }
+    }
+
+    fn conducts(&self, idx: usize) -> bool {
+        self.data[idx].as_ref().is_some_and(|material| material.conducts())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Metal,
+        Particle,
+    };
+
+    use super::*;
+
+    #[test]
+    fn charge_runs_down_a_wire() {
+        let length = 20;
+        let mut grid = Grid::new(length, 2);
+        for x in 0 .. length as i32 {
+            grid[Point::new(x, 1)] = Some(Particle::Metal(Metal));
+        }
+        let far_end = Point::new(length as i32 - 1, 1);
+
+        grid.electrify(Point::new(0, 1));
+        grid.run(length - 2);
+        assert_eq!(grid.charge_at(far_end), 0);
+
+        grid.update();
+        assert!(grid.charge_at(far_end) > 0);
+        assert_eq!(grid.charge_at(Point::new(0, 0)), 0);
+    }
+
+    #[test]
+    fn a_fed_wire_stays_live_past_full_charge() {
+        let length = 3 * FULL_CHARGE as usize;
+        let mut grid = Grid::new(length, 1);
+        for x in 0 .. length as i32 {
+            grid[Point::new(x, 0)] = Some(Particle::Metal(Metal));
+        }
+        let far_end = Point::new(length as i32 - 1, 0);
+
+        // Kept fed, as holding E does.
+        for _ in 0 .. length {
+            grid.electrify(Point::new(0, 0));
+            grid.update();
+        }
+        assert_eq!(grid.charge_at(far_end), FULL_CHARGE);
+
+        // Once the feed stops the whole wire fades together, however long
+        // it is.
+        grid.run(FULL_CHARGE as usize + 1);
+        assert!((0 .. length as i32).all(|x| grid.charge_at(Point::new(x, 0)) == 0));
+    }
+
+    #[test]
+    fn charge_fades_without_a_source() {
+        let mut grid = Grid::new(3, 1);
+        for x in 0 .. 3 {
+            grid[Point::new(x, 0)] = Some(Particle::Metal(Metal));
+        }
+
+        grid.electrify(Point::new(1, 0));
+        grid.run(FULL_CHARGE as usize + 1);
+
+        assert!((0 .. 3).all(|x| grid.charge_at(Point::new(x, 0)) == 0));
+    }
+}
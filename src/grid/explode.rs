@@ -0,0 +1,51 @@
+use crate::{
+    Fire,
+    Grid,
+    Particle,
+    Point,
+};
+
+use super::circle;
+
+impl Grid {
+    /// Blows apart everything loose within `radius` of `center` and sets
+    /// whatever is flammable there alight. Solids like stone are left
+    /// standing. Flames fill the edge of the blast, and explosives caught in
+    /// it go off in turn.
+    pub fn explode(&mut self, center: Point, radius: i32) {
+        let mut blasts = vec![(center, radius)];
+
+        while let Some((center, radius)) = blasts.pop() {
+            for point in circle(center, radius) {
+                let idx = match self.index_of(point) {
+                    Some(idx) => idx,
+                    None => continue,
+                };
+                let edge = {
+                    let (dx, dy) = (point.x - center.x, point.y - center.y);
+                    dx * dx + dy * dy > (radius - 1) * (radius - 1)
+                };
+
+                match &self.data[idx] {
+                    Some(material) if material.is_static() && material.flammable() => {
+                        self.place(idx, Particle::Fire(Fire::new()));
+                    },
+                    Some(material) if material.is_static() => {},
+                    Some(material) => {
+                        if let Some(chained) = material.blast_radius() {
+                            if point != center {
+                                blasts.push((point, chained));
+                            }
+                        }
+                        self.replace(idx, None);
+                    },
+                    None => {},
+                }
+
+                if edge && self.data[idx].is_none() {
+                    self.place(idx, Particle::Fire(Fire::new()));
+                }
+            }
+        }
+    }
+}
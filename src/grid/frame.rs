@@ -0,0 +1,176 @@
+use std::sync::{
+    Arc,
+    Mutex,
+};
+
+use crate::{
+    Color,
+    Grid,
+    Point,
+    Renderer,
+};
+
+/// What the grid looked like after one tick, as the colors it would be drawn
+/// in, taken by [`Grid::frame`]. Frames never change once taken, so another
+/// thread can draw one while the simulation moves on. They hold colors
+/// rather than the cells themselves, so taking one never clones a particle.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GridFrame {
+    /// Each cell's color in row order, with `None` for empty cells.
+    colors: Vec<Option<Color>>,
+    width: usize,
+    height: usize,
+    /// Where the frame came in the order frames were published, see
+    /// [`FrameSlot::publish`].
+    sequence: u64,
+}
+
+impl GridFrame {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// How many frames were published to the slot before this one, or `0`
+    /// for a frame that was never published.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// The color of the cell at `point`, as [`Grid::color_at`] gave it when
+    /// the frame was taken.
+    pub fn color_at(&self, point: Point) -> Option<Color> {
+        let in_bounds = point.x >= 0 && (point.x as usize) < self.width &&
+            point.y >= 0 && (point.y as usize) < self.height;
+        if !in_bounds { return None; }
+
+        self.colors[point.y as usize * self.width + point.x as usize]
+    }
+
+    /// Draws every visible particle onto a freshly cleared `renderer`, as
+    /// [`Grid::draw`] would have.
+    pub fn draw(&self, renderer: &mut dyn Renderer) -> Result<(), String> {
+        let cells: Vec<_> = self.colors.iter()
+            .enumerate()
+            .filter_map(|(idx, color)| {
+                let point = Point::new((idx % self.width) as i32, (idx / self.width) as i32);
+                color.filter(|_| renderer.is_visible(point)).map(|color| (point, color))
+            })
+            .collect();
+
+        renderer.clear()?;
+        renderer.fill_cells(&cells)?;
+        renderer.present()
+    }
+}
+
+/// Hands frames from the simulation thread to a rendering thread. The
+/// simulation publishes a finished frame; the renderer takes the latest one
+/// and draws it at its own pace.
+///
+/// Frames are immutable and shared through an [`Arc`], so publishing only
+/// swaps which frame the slot points to, and taking a frame only clones that
+/// pointer. The lock is held for nothing longer than that swap or clone,
+/// never while a frame is built or drawn, so neither side waits on the
+/// other's work, and the renderer always holds a whole frame: the one the
+/// slot pointed to, however many are published while it draws.
+#[derive(Debug)]
+pub struct FrameSlot {
+    latest: Mutex<Arc<GridFrame>>,
+}
+
+impl FrameSlot {
+    /// A slot starting out with `frame`.
+    pub fn new(frame: GridFrame) -> FrameSlot {
+        FrameSlot { latest: Mutex::new(Arc::new(frame)) }
+    }
+
+    /// Makes `frame` the latest, numbering it one after the frame it
+    /// replaces.
+    pub fn publish(&self, mut frame: GridFrame) {
+        let mut latest = self.latest.lock().expect("a thread panicked holding the frame slot");
+        frame.sequence = latest.sequence + 1;
+        *latest = Arc::new(frame);
+    }
+
+    /// The most recently published frame.
+    pub fn latest(&self) -> Arc<GridFrame> {
+        Arc::clone(&self.latest.lock().expect("a thread panicked holding the frame slot"))
+    }
+}
+
+impl Grid {
+    /// Captures how the grid would be drawn right now, for drawing later or
+    /// on another thread. Costs one pass over the grid, with no particles
+    /// cloned.
+    pub fn frame(&self) -> GridFrame {
+        GridFrame {
+            colors: (0 .. self.data.len()).map(|idx| self.color_at(self.point_of(idx))).collect(),
+            width: self.width,
+            height: self.height,
+            sequence: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use crate::{
+        Particle,
+        Sand,
+        Stone,
+    };
+
+    use super::*;
+
+    #[test]
+    fn a_frame_keeps_what_the_grid_looked_like() {
+        let mut grid = Grid::new(2, 2);
+        grid[Point::new(0, 0)] = Some(Particle::Stone(Stone));
+        let color = grid.color_at(Point::new(0, 0));
+
+        let frame = grid.frame();
+        grid.clear();
+
+        assert_eq!(frame.color_at(Point::new(0, 0)), color);
+        assert_eq!(frame.color_at(Point::new(1, 1)), None);
+        assert_eq!(frame.color_at(Point::new(2, 0)), None);
+    }
+
+    #[test]
+    fn the_renderer_only_ever_sees_whole_frames() {
+        let mut grid = Grid::with_seed(32, 32, 4);
+        grid.paint_circle(Point::new(16, 8), 6, &Sand);
+        let particles = grid.count_filled();
+        let slot = FrameSlot::new(grid.frame());
+
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                for _ in 0 .. 200 {
+                    grid.update();
+                    slot.publish(grid.frame());
+                }
+            });
+
+            // Sand is only ever moved, never created or destroyed, so a
+            // frame caught halfway through a tick would show the wrong
+            // number of grains.
+            let mut last = 0;
+            while last < 200 {
+                let frame = slot.latest();
+                assert!(frame.sequence() >= last);
+                last = frame.sequence();
+
+                let grains = (0 .. 32 * 32)
+                    .filter(|&idx| frame.color_at(Point::new(idx % 32, idx / 32)).is_some())
+                    .count();
+                assert_eq!(grains, particles);
+            }
+        });
+    }
+}
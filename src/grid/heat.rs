@@ -0,0 +1,224 @@
+use std::mem;
+
+use crate::{
+    Color,
+    Grid,
+    Point,
+};
+
+use super::NEIGHBOURS;
+
+/// Temperature every cell starts at and slowly returns to, in degrees
+/// Celsius.
+pub(crate) const AMBIENT: f32 = 20.0;
+
+/// [`Material::heat_transfer`](crate::Material::heat_transfer) of an empty
+/// cell. Air insulates, so heat mostly travels through materials.
+const AIR_HEAT_TRANSFER: f32 = 0.05;
+
+/// Share of the difference exchanged with each neighbour per tick between
+/// two perfect conductors. Four neighbours at this rate can never overshoot.
+const DIFFUSION_RATE: f32 = 0.25;
+
+/// Share of an empty cell's excess heat lost per tick, as air carries it
+/// away, so that heat sources don't warm the grid forever. Materials only
+/// lose heat through the air around them, so ice keeps its cold for a while.
+const COOLING: f32 = 0.002;
+
+/// Cells within this many degrees of ambient are snapped back to it and no
+/// longer diffused, keeping the cost of heat proportional to the warm area.
+const SETTLED: f32 = 0.05;
+
+/// Colors of [`View::Temperature`](super::View::Temperature) at rising
+/// temperatures, blended between neighbouring entries. Ambient sits low on
+/// the scale, where most of the interesting heat is.
+const GRADIENT: [(f32, Color); 5] = [
+    (-50.0, Color::rgb(0, 0, 128)),
+    (AMBIENT, Color::rgb(0, 96, 255)),
+    (100.0, Color::rgb(160, 0, 224)),
+    (300.0, Color::rgb(255, 0, 96)),
+    (1000.0, Color::rgb(255, 0, 0)),
+];
+
+/// Where `temperature` falls on [`GRADIENT`], clamped to its ends.
+pub(super) fn color(temperature: f32) -> Color {
+    let (coldest, hottest) = (GRADIENT[0], GRADIENT[GRADIENT.len() - 1]);
+    if temperature <= coldest.0 { return coldest.1; }
+
+    GRADIENT.windows(2)
+        .find(|pair| temperature <= pair[1].0)
+        .map_or(hottest.1, |pair| {
+            let ((low, from), (high, to)) = (pair[0], pair[1]);
+            let t = (temperature - low) / (high - low);
+            let blend = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+            Color::rgb(blend(from.r, to.r), blend(from.g, to.g), blend(from.b, to.b))
+        })
+}
+
+impl Grid {
+    /// Temperature of the cell at `point`, or ambient off the grid.
+    pub fn temperature_at(&self, point: Point) -> f32 {
+        self.index_of(point).map_or(AMBIENT, |idx| self.temperature[idx])
+    }
+
+    /// Sets the temperature of the cell at `point`. Off-grid points are
+    /// ignored.
+    pub fn set_temperature(&mut self, point: Point, temperature: f32) {
+        if let Some(idx) = self.index_of(point) {
+            self.set_heat(idx, temperature);
+        }
+    }
+
+    /// Moves temperature one tick towards the neighbours' and ambient. Only
+    /// warm cells and their neighbours can change, so nothing else is
+    /// visited.
+    pub(super) fn diffuse_heat(&mut self) {
+        let mut warm = self.warm.take_sorted();
+        let mut updates = mem::take(&mut self.heat_updates);
+
+        // All new temperatures are worked out from the old ones before any
+        // are written. Cells next to several warm ones are listed more than
+        // once, but always with the same value.
+        for &idx in &warm {
+            updates.push((idx, self.diffused(idx)));
+
+            let position = self.point_of(idx);
+            for &(dx, dy) in &NEIGHBOURS {
+                if let Some(neighbour) = self.index_of(position.offset(dx, dy)) {
+                    updates.push((neighbour, self.diffused(neighbour)));
+                }
+            }
+        }
+
+        for &(idx, temperature) in &updates {
+            self.set_heat(idx, temperature);
+            self.change_phase(idx);
+        }
+
+        updates.clear();
+        self.heat_updates = updates;
+        warm.clear();
+        self.warm.recycle(warm);
+    }
+
+    pub(super) fn set_heat(&mut self, idx: usize, temperature: f32) {
+        self.temperature[idx] = temperature;
+        self.track_heat(idx);
+    }
+
+    /// Replaces the particle at `idx` if it changes phase at its cell's
+    /// temperature.
+    pub(super) fn change_phase(&mut self, idx: usize) {
+        let changed = self.data[idx].as_ref()
+            .and_then(|material| material.phase_change(self.temperature[idx]));
+        if let Some(changed) = changed {
+            self.data[idx] = Some(changed);
+            self.touch(idx);
+        }
+    }
+
+    /// Adds `heat` degrees to the cell at `idx`.
+    pub(super) fn heat(&mut self, idx: usize, heat: f32) {
+        self.temperature[idx] += heat;
+        self.track_heat(idx);
+    }
+
+    /// Carries temperature along with a particle moving between two cells.
+    pub(super) fn swap_heat(&mut self, a: usize, b: usize) {
+        self.temperature.swap(a, b);
+        self.track_heat(a);
+        self.track_heat(b);
+    }
+
+    /// Keeps `idx` in the warm set while it differs from ambient, and snaps
+    /// it back once it no longer does.
+    pub(super) fn track_heat(&mut self, idx: usize) {
+        if (self.temperature[idx] - AMBIENT).abs() > SETTLED {
+            let point = self.point_of(idx);
+            self.warm.mark(idx, point);
+        } else {
+            self.temperature[idx] = AMBIENT;
+        }
+    }
+
+    fn diffused(&self, idx: usize) -> f32 {
+        let temperature = self.temperature[idx];
+        let conductivity = self.conductivity(idx);
+        let position = self.point_of(idx);
+
+        let mut flow = 0.0;
+        for &(dx, dy) in &NEIGHBOURS {
+            if let Some(neighbour) = self.index_of(position.offset(dx, dy)) {
+                let rate = DIFFUSION_RATE * (conductivity + self.conductivity(neighbour)) / 2.0;
+                flow += rate * (self.temperature[neighbour] - temperature);
+            }
+        }
+
+        let temperature = temperature + flow;
+        if self.data[idx].is_none() {
+            temperature + (AMBIENT - temperature) * COOLING
+        } else {
+            temperature
+        }
+    }
+
+    fn conductivity(&self, idx: usize) -> f32 {
+        self.data[idx].as_ref().map_or(AIR_HEAT_TRANSFER, |material| material.heat_transfer())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Fire,
+        Particle,
+        Stone,
+    };
+
+    #[test]
+    fn hot_cell_warms_cold_neighbour() {
+        let mut grid = Grid::new(3, 1);
+        for x in 0 .. 3 {
+            grid[Point::new(x, 0)] = Some(Particle::Stone(Stone));
+        }
+        grid.set_temperature(Point::new(0, 0), 500.0);
+
+        for _ in 0 .. 10 {
+            grid.update();
+        }
+
+        let (hot, middle, far) = (
+            grid.temperature_at(Point::new(0, 0)),
+            grid.temperature_at(Point::new(1, 0)),
+            grid.temperature_at(Point::new(2, 0)),
+        );
+        assert!(hot < 500.0);
+        assert!(hot > middle && middle > far && far > AMBIENT, "{} {} {}", hot, middle, far);
+    }
+
+    #[test]
+    fn heat_fades_back_to_ambient() {
+        let mut grid = Grid::new(3, 3);
+        grid.set_temperature(Point::new(1, 1), 100.0);
+
+        for _ in 0 .. 5000 {
+            grid.update();
+        }
+
+        assert!(grid.warm.indices.is_empty());
+        assert_eq!(grid.temperature_at(Point::new(1, 1)), AMBIENT);
+    }
+
+    #[test]
+    fn fire_heats_its_surroundings() {
+        let mut grid = Grid::new(3, 3);
+        grid[Point::new(1, 1)] = Some(Particle::Fire(Fire::new()));
+
+        for _ in 0 .. 10 {
+            grid.update();
+        }
+
+        assert!(grid.temperature_at(Point::new(1, 0)) > AMBIENT);
+    }
+}
@@ -0,0 +1,132 @@
+use std::path::Path;
+
+use image::{
+    error::{
+        ParameterError,
+        ParameterErrorKind,
+    },
+    ImageError,
+    ImageResult,
+};
+
+use crate::{
+    Color,
+    Grid,
+    Particle,
+};
+
+/// Pixels closest to this color leave their cell empty, matching how
+/// [`Grid::to_rgb_buffer`] renders empty cells.
+const BACKGROUND: Color = Color::rgb(0, 0, 0);
+
+impl Grid {
+    /// Builds a `width` by `height` grid from the image at `path`, scaling it
+    /// to fit. Each pixel becomes a copy of the material whose palette color
+    /// is nearest, or stays empty if black is nearer still.
+    pub fn from_image(
+        path: &Path,
+        width: usize,
+        height: usize,
+        palette: &[(Color, Particle)],
+    ) -> ImageResult<Grid> {
+        let mut grid = Grid::try_new(width, height).map_err(|error| ImageError::Parameter(
+            ParameterError::from_kind(ParameterErrorKind::Generic(error.to_string())),
+        ))?;
+        let image = image::open(path)?.into_rgb8();
+        let (image_width, image_height) = image.dimensions();
+
+        for y in 0 .. height {
+            for x in 0 .. width {
+                let pixel = image.get_pixel(
+                    (x * image_width as usize / width) as u32,
+                    (y * image_height as usize / height) as u32,
+                );
+                let color = Color::rgb(pixel[0], pixel[1], pixel[2]);
+
+                let nearest = palette.iter()
+                    .min_by_key(|(entry, _)| distance(*entry, color))
+                    .filter(|(entry, _)| distance(*entry, color) < distance(BACKGROUND, color));
+                if let Some((_, material)) = nearest {
+                    grid.place(y * width + x, *material);
+                }
+            }
+        }
+
+        Ok(grid)
+    }
+}
+
+/// Squared euclidean distance between two colors in RGB space.
+fn distance(a: Color, b: Color) -> u32 {
+    let channel = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    channel(a.r, b.r) + channel(a.g, b.g) + channel(a.b, b.b)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env,
+        fs,
+    };
+
+    use image::{
+        Rgb,
+        RgbImage,
+    };
+
+    use super::*;
+    use crate::{
+        Material,
+        Point,
+        Sand,
+        Stone,
+    };
+
+    fn palette() -> Vec<(Color, Particle)> {
+        vec![
+            (Sand.color(), Particle::Sand(Sand)),
+            (Stone.color(), Particle::Stone(Stone)),
+        ]
+    }
+
+    /// A 2x2 image: sand-ish top left, stone-ish bottom right, black elsewhere.
+    fn load_scaled(width: usize, height: usize) -> Grid {
+        let mut image = RgbImage::new(2, 2);
+        image.put_pixel(0, 0, Rgb([200, 180, 120]));
+        image.put_pixel(1, 1, Rgb([110, 110, 115]));
+
+        let path = env::temp_dir().join(format!("into_the_breach-{}-{}x{}.png",
+            std::process::id(), width, height));
+        image.save(&path).unwrap();
+        let grid = Grid::from_image(&path, width, height, &palette()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        grid
+    }
+
+    fn id_at(grid: &Grid, x: i32, y: i32) -> Option<u16> {
+        grid[Point::new(x, y)].as_ref().map(|m| m.id())
+    }
+
+    #[test]
+    fn maps_pixels_to_nearest_material() {
+        let grid = load_scaled(2, 2);
+
+        assert_eq!(id_at(&grid, 0, 0), Some(Sand.id()));
+        assert_eq!(id_at(&grid, 1, 1), Some(Stone.id()));
+        assert_eq!(id_at(&grid, 1, 0), None);
+        assert_eq!(id_at(&grid, 0, 1), None);
+    }
+
+    #[test]
+    fn scales_image_to_grid() {
+        let grid = load_scaled(4, 4);
+
+        assert_eq!(id_at(&grid, 1, 1), Some(Sand.id()));
+        assert_eq!(id_at(&grid, 3, 2), Some(Stone.id()));
+        assert_eq!(id_at(&grid, 2, 1), None);
+
+        let grid = load_scaled(1, 1);
+        assert_eq!(id_at(&grid, 0, 0), Some(Sand.id()));
+    }
+}
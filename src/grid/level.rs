@@ -0,0 +1,152 @@
+use std::{
+    error::Error,
+    fmt,
+};
+
+use ron::{
+    error::SpannedError,
+    ser::PrettyConfig,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    Grid,
+    GridError,
+    MaterialRegistry,
+    Point,
+};
+
+/// The text form of a grid: its size and each occupied cell with the name
+/// of its material. Empty cells are left out.
+#[derive(Serialize, Deserialize)]
+struct Level {
+    width: usize,
+    height: usize,
+    cells: Vec<(i32, i32, String)>,
+}
+
+/// Why a level couldn't be read by [`Grid::from_ron`].
+#[derive(Debug)]
+pub enum LevelError {
+    /// The text isn't a valid level.
+    Syntax(SpannedError),
+    /// A cell names a material the registry doesn't have.
+    UnknownMaterial(String),
+    /// A cell lies outside the level's own dimensions.
+    OutOfBounds(Point),
+    /// The level's dimensions aren't a size a grid can have.
+    Size(GridError),
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Syntax(error) => write!(f, "{}", error),
+            LevelError::UnknownMaterial(name) => write!(f, "unknown material {:?}", name),
+            LevelError::OutOfBounds(point) => {
+                write!(f, "cell ({}, {}) is outside the grid", point.x, point.y)
+            },
+            LevelError::Size(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for LevelError {}
+
+impl Grid {
+    /// Writes the grid as a hand-editable RON level, naming materials as
+    /// [`MaterialRegistry::builtin`] does.
+    pub fn to_ron(&self) -> String {
+        self.to_ron_with(&MaterialRegistry::builtin())
+    }
+
+    /// Like [`Grid::to_ron`], naming materials through `registry`. Particles
+    /// of materials it has no name for are left out.
+    pub fn to_ron_with(&self, registry: &MaterialRegistry) -> String {
+        let level = Level {
+            width: self.width,
+            height: self.height,
+            cells: self.iter_filled()
+                .filter_map(|(point, material)| {
+                    registry.name_of(material).map(|name| (point.x, point.y, name.to_string()))
+                })
+                .collect(),
+        };
+
+        ron::ser::to_string_pretty(&level, PrettyConfig::new())
+            .expect("levels only hold numbers and strings")
+    }
+
+    /// Reads a grid back from a level written by [`Grid::to_ron`].
+    pub fn from_ron(text: &str) -> Result<Grid, LevelError> {
+        Grid::from_ron_with(text, &MaterialRegistry::builtin())
+    }
+
+    /// Like [`Grid::from_ron`], creating materials through `registry`.
+    pub fn from_ron_with(text: &str, registry: &MaterialRegistry) -> Result<Grid, LevelError> {
+        let level: Level = ron::from_str(text).map_err(LevelError::Syntax)?;
+        let mut grid = Grid::try_new(level.width, level.height).map_err(LevelError::Size)?;
+
+        for (x, y, name) in level.cells {
+            let point = Point::new(x, y);
+            let idx = grid.index_of(point).ok_or(LevelError::OutOfBounds(point))?;
+            let material = registry.create(&name).ok_or(LevelError::UnknownMaterial(name))?;
+            grid.place(idx, material);
+        }
+
+        grid.wake_all();
+        Ok(grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Lava,
+        Particle,
+        Sand,
+        Stone,
+    };
+
+    fn occupied(grid: &Grid) -> Vec<(Point, u16)> {
+        grid.iter_filled().map(|(point, material)| (point, material.id())).collect()
+    }
+
+    #[test]
+    fn round_trips_occupied_cells() {
+        let mut grid = Grid::new(6, 4);
+        grid[Point::new(0, 0)] = Some(Particle::Sand(Sand));
+        grid[Point::new(5, 3)] = Some(Particle::Stone(Stone));
+        grid[Point::new(2, 1)] = Some(Particle::Lava(Lava));
+
+        let loaded = Grid::from_ron(&grid.to_ron()).unwrap();
+
+        assert_eq!((loaded.width(), loaded.height()), (6, 4));
+        assert_eq!(occupied(&loaded), occupied(&grid));
+    }
+
+    #[test]
+    fn empty_grids_have_no_cells() {
+        let text = Grid::new(3, 2).to_ron();
+        let level: Level = ron::from_str(&text).unwrap();
+
+        assert!(level.cells.is_empty());
+        assert_eq!(Grid::from_ron(&text).unwrap().count_filled(), 0);
+    }
+
+    #[test]
+    fn rejects_unknown_materials_and_stray_cells() {
+        let unknown = "(width: 2, height: 2, cells: [(0, 0, \"unobtainium\")])";
+        assert!(matches!(Grid::from_ron(unknown), Err(LevelError::UnknownMaterial(_))));
+
+        let stray = "(width: 2, height: 2, cells: [(2, 0, \"sand\")])";
+        assert!(matches!(Grid::from_ron(stray), Err(LevelError::OutOfBounds(_))));
+
+        let empty = "(width: 0, height: 2, cells: [])";
+        assert!(matches!(Grid::from_ron(empty), Err(LevelError::Size(GridError::Empty { .. }))));
+    }
+}
@@ -0,0 +1,139 @@
+use std::mem;
+
+use crate::{
+    Grid,
+    Life,
+    Material,
+    Particle,
+    Point,
+};
+
+/// The rules [`Grid::update`] advances the grid by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Falling sand: particles move, burn, melt and react.
+    Physics,
+    /// Conway's Game of Life over the [`Life`] cells. Everything else stays
+    /// put and counts as dead.
+    Life,
+}
+
+/// Classic Life patterns, for [`Grid::paint_pattern`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pattern {
+    /// Three cells in a row, flipping between horizontal and vertical.
+    Blinker,
+    /// Five cells that walk diagonally down and to the right.
+    Glider,
+}
+
+impl Pattern {
+    /// Offsets of the pattern's live cells from its top-left corner.
+    fn cells(self) -> &'static [(i32, i32)] {
+        match self {
+            Pattern::Blinker => &[(0, 0), (1, 0), (2, 0)],
+            Pattern::Glider => &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)],
+        }
+    }
+}
+
+impl Grid {
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Switches the rules the grid follows. Everything is woken when
+    /// switching back to physics, since nothing moved in the meantime.
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+        if mode == Mode::Physics {
+            self.wake_all();
+        }
+    }
+
+    /// Brings `pattern` to life with its top-left corner at `origin`. Cells
+    /// falling off the grid are dropped.
+    pub fn paint_pattern(&mut self, pattern: Pattern, origin: Point) {
+        for &(dx, dy) in pattern.cells() {
+            if let Some(idx) = self.index_of(origin.offset(dx, dy)) {
+                self.place(idx, Particle::Life(Life));
+            }
+        }
+    }
+
+    /// Advances every cell by one generation of Life. Births and deaths are
+    /// all worked out from the current generation before any are applied.
+    pub(super) fn generation(&mut self) {
+        let mut next = mem::take(&mut self.generation);
+        next.clear();
+        next.extend((0 .. self.data.len()).map(|idx| {
+            let point = self.point_of(idx);
+            let neighbours = (-1 ..= 1)
+                .flat_map(|dy| (-1 ..= 1).map(move |dx| (dx, dy)))
+                .filter(|&offset| offset != (0, 0))
+                .filter(|&(dx, dy)| self.is_alive(point.offset(dx, dy)))
+                .count();
+
+            match (self.is_alive(point), neighbours) {
+                (true, 2) | (true, 3) => Some(true),
+                (true, _) => Some(false),
+                (false, 3) if self.data[idx].is_none() => Some(true),
+                _ => None,
+            }
+        }));
+
+        for (idx, &alive) in next.iter().enumerate() {
+            match alive {
+                Some(true) if self.data[idx].is_none() => self.place(idx, Particle::Life(Life)),
+                Some(false) => self.replace(idx, None),
+                _ => {},
+            }
+        }
+
+        self.generation = next;
+    }
+
+    fn is_alive(&self, point: Point) -> bool {
+        self.get(point)
+            .and_then(|cell| cell.as_ref())
+            .is_some_and(|material| material.id() == Life.id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alive(grid: &Grid) -> Vec<Point> {
+        (0 .. grid.height() as i32)
+            .flat_map(|y| (0 .. grid.width() as i32).map(move |x| Point::new(x, y)))
+            .filter(|&point| grid.is_alive(point))
+            .collect()
+    }
+
+    #[test]
+    fn blinker_flips_each_generation() {
+        let mut grid = Grid::new(5, 5);
+        grid.set_mode(Mode::Life);
+        grid.paint_pattern(Pattern::Blinker, Point::new(1, 2));
+
+        grid.update();
+        assert_eq!(alive(&grid), [Point::new(2, 1), Point::new(2, 2), Point::new(2, 3)]);
+
+        grid.update();
+        assert_eq!(alive(&grid), [Point::new(1, 2), Point::new(2, 2), Point::new(3, 2)]);
+    }
+
+    #[test]
+    fn glider_moves_diagonally() {
+        let mut grid = Grid::new(8, 8);
+        grid.set_mode(Mode::Life);
+        grid.paint_pattern(Pattern::Glider, Point::new(0, 0));
+        let start = alive(&grid);
+
+        grid.run(4);
+
+        let moved: Vec<_> = start.iter().map(|point| point.offset(1, 1)).collect();
+        assert_eq!(alive(&grid), moved);
+    }
+}
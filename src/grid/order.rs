@@ -0,0 +1,131 @@
+use rand::Rng;
+
+use crate::Grid;
+
+/// How the particles of a tick take their turns to move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpdateOrder {
+    /// Every particle chooses its move against the grid as it stood at the
+    /// start of the tick, and the moves are applied in scan order, with the
+    /// first to claim a cell getting it. Choosing can be spread across
+    /// threads, but a stack only falls one grain at a time from the bottom,
+    /// and contested cells go to whichever particle is further up and left.
+    Simultaneous,
+    /// Particles move one at a time, lowest first, each choosing after
+    /// those beneath have moved, so a stack falls together. Each row is
+    /// taken in a direction picked at random every tick, so neither side is
+    /// favoured. Runs on one thread, even under [`Grid::update_parallel`].
+    BottomUp,
+}
+
+impl Grid {
+    pub fn update_order(&self) -> UpdateOrder {
+        self.order
+    }
+
+    pub fn set_update_order(&mut self, order: UpdateOrder) {
+        self.order = order;
+    }
+
+    /// Moves the cells in `active` under [`UpdateOrder::BottomUp`], with
+    /// each particle's random choices drawn as under
+    /// [`UpdateOrder::Simultaneous`] and the row direction from the grid's
+    /// own generator, so seeded grids still replay identically.
+    pub(super) fn move_bottom_up(&mut self, active: &[usize], seed: u64) {
+        let (gx, gy) = self.gravity;
+        // Along each row, measured at right angles to gravity.
+        let across = if self.rng.gen() { (gy, -gx) } else { (-gy, gx) };
+
+        let mut order = active.to_vec();
+        order.sort_by_cached_key(|&idx| {
+            let point = self.point_of(idx);
+            (self.height_of(point), point.x * across.0 + point.y * across.1)
+        });
+
+        // Particles already moved this tick, which mustn't be moved again
+        // when their new cell's turn comes.
+        let mut moved = Vec::new();
+        for idx in order {
+            if self.claimed[idx] { continue; }
+            let mv = match self.choose(idx, seed) {
+                Some(mv) => mv,
+                None => continue,
+            };
+
+            match mv.to {
+                Some(new_idx) if new_idx == idx => self.velocity[idx] = mv.velocity,
+                // Anything in the way was chosen against the grid as it is
+                // now, so is lighter and simply swaps places.
+                Some(new_idx) => {
+                    self.shift(idx, new_idx, mv.velocity);
+                    self.claimed[new_idx] = true;
+                    moved.push(new_idx);
+                },
+                None => self.replace(idx, None),
+            }
+        }
+
+        for idx in moved {
+            self.claimed[idx] = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Particle,
+        Point,
+        Sand,
+        Water,
+    };
+
+    use super::*;
+
+    /// A column of four grains at the top of a tall, narrow grid.
+    fn column(order: UpdateOrder) -> Grid {
+        let mut grid = Grid::new(1, 12);
+        grid.set_update_order(order);
+        for y in 0 .. 4 {
+            grid[Point::new(0, y)] = Some(Particle::Sand(Sand));
+        }
+        grid
+    }
+
+    fn filled_rows(grid: &Grid) -> Vec<i32> {
+        grid.iter_filled().map(|(point, _)| point.y).collect()
+    }
+
+    #[test]
+    fn a_column_falls_together_bottom_up() {
+        let mut grid = column(UpdateOrder::BottomUp);
+
+        grid.update();
+
+        assert_eq!(filled_rows(&grid), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn a_column_peels_away_from_the_bottom_simultaneously() {
+        let mut grid = column(UpdateOrder::Simultaneous);
+
+        grid.update();
+
+        // Only the bottom grain saw room to fall; the top one lags behind.
+        assert_eq!(filled_rows(&grid), [0, 1, 2, 4]);
+    }
+
+    #[test]
+    fn bottom_up_replays_under_a_seed() {
+        let run = || {
+            let mut grid = Grid::with_seed(24, 16, 8);
+            grid.set_update_order(UpdateOrder::BottomUp);
+            grid.paint_circle(Point::new(8, 4), 3, &Sand);
+            grid.paint_circle(Point::new(16, 6), 3, &Water);
+            grid.run(40);
+            grid.to_rgb_buffer()
+        };
+
+        assert_eq!(run(), run());
+    }
+}
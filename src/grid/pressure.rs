@@ -0,0 +1,91 @@
+use crate::{
+    Grid,
+    Point,
+};
+
+use super::NEIGHBOURS;
+
+impl Grid {
+    /// Evens out the levels of connected bodies of liquid that
+    /// [find their level](crate::Material::finds_level), as the pressure of a
+    /// taller column pushes a shorter one up. Each body holding an `active`
+    /// cell hands one particle a tick from its highest open surface to the
+    /// top of its lowest, so water poured down one arm of a U bend climbs the
+    /// other until both stand level.
+    ///
+    /// Bodies are found with `claimed` as scratch flags, so this must run
+    /// after a tick's moves are applied and their claims cleared.
+    pub(super) fn equalize(&mut self, active: &[usize]) {
+        let mut body = Vec::new();
+        let mut visited = Vec::new();
+
+        for &idx in active {
+            if self.claimed[idx] { continue; }
+            let id = match &self.data[idx] {
+                Some(material) if material.finds_level() => material.id(),
+                _ => continue,
+            };
+
+            let (mut highest, mut lowest) = (None, None);
+            self.claimed[idx] = true;
+            body.push(idx);
+            while let Some(cell) = body.pop() {
+                visited.push(cell);
+                let point = self.point_of(cell);
+
+                if let Some(None) = self.get(self.above(point)) {
+                    let height = self.height_of(point);
+                    if highest.is_none_or(|(top, _)| height > top) {
+                        highest = Some((height, cell));
+                    }
+                    if lowest.is_none_or(|(bottom, _)| height < bottom) {
+                        lowest = Some((height, cell));
+                    }
+                }
+
+                for &(dx, dy) in &NEIGHBOURS {
+                    let neighbor = match self.index_of(point.offset(dx, dy)) {
+                        Some(neighbor) => neighbor,
+                        None => continue,
+                    };
+                    let same = self.data[neighbor].as_ref().is_some_and(|m| m.id() == id);
+                    if same && !self.claimed[neighbor] {
+                        self.claimed[neighbor] = true;
+                        body.push(neighbor);
+                    }
+                }
+            }
+
+            // A difference of a single cell would only swap which side is
+            // higher.
+            if let (Some((top, from)), Some((bottom, under))) = (highest, lowest) {
+                if top - bottom >= 2 {
+                    let to = self.index_of(self.above(self.point_of(under)))
+                        .expect("open surfaces have a cell above them");
+                    let moved = self.data.take(from);
+                    self.data.set(to, moved);
+                    self.shade.swap(from, to);
+                    self.swap_heat(from, to);
+                    self.velocity[from] = 0.0;
+                    self.velocity[to] = 0.0;
+                    self.touch(from);
+                    self.touch(to);
+                }
+            }
+        }
+
+        for cell in visited {
+            self.claimed[cell] = false;
+        }
+    }
+
+    /// The cell directly against gravity from `point`.
+    fn above(&self, point: Point) -> Point {
+        point.offset(-self.gravity.0, -self.gravity.1)
+    }
+
+    /// How far up `point` is, measured against gravity.
+    pub(super) fn height_of(&self, point: Point) -> i32 {
+        -(point.x * self.gravity.0 + point.y * self.gravity.1)
+    }
+}
@@ -0,0 +1,245 @@
+use crate::{
+    Color,
+    Grid,
+    Palette,
+    Point,
+};
+
+/// Laid over awake cells while [`Grid::show_awake`] is on.
+const AWAKE_TINT: Color = Color::rgb(0, 255, 0);
+
+/// How much of `AWAKE_TINT` shows through, enough to stand out without
+/// hiding what the cell holds.
+const AWAKE_OPACITY: f32 = 0.4;
+
+/// What [`Grid::color_at`] colors cells by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum View {
+    /// Each particle's material color, shaded and heated.
+    Materials,
+    /// Each particle's temperature, from blue for cold to red for hot, for
+    /// watching heat spread.
+    Temperature,
+}
+
+/// Something the grid can be drawn onto cell by cell, such as a window or an
+/// image. Errors are reported as messages, to be logged by the caller.
+pub trait Renderer {
+    /// Empties the whole drawing, ready for a full redraw.
+    fn clear(&mut self) -> Result<(), String>;
+
+    /// Draws each cell in its color.
+    fn fill_cells(&mut self, cells: &[(Point, Color)]) -> Result<(), String>;
+
+    /// Draws each cell as empty.
+    fn clear_cells(&mut self, cells: &[Point]) -> Result<(), String>;
+
+    /// Finishes the frame once everything has been drawn.
+    fn present(&mut self) -> Result<(), String>;
+
+    /// Whether `point` would be seen at all. Cells that wouldn't are never
+    /// passed to the renderer, so views of part of the grid stay cheap.
+    fn is_visible(&self, _point: Point) -> bool {
+        true
+    }
+}
+
+impl Grid {
+    pub fn view(&self) -> View {
+        self.view
+    }
+
+    /// Switches what cells are colored by. Nothing is marked dirty, so
+    /// callers rendering incrementally should follow this with a full
+    /// redraw.
+    pub fn set_view(&mut self, view: View) {
+        self.view = view;
+    }
+
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    /// Draws the materials `palette` lists in its colors instead of their
+    /// own, replacing any glow they have when hot. As with
+    /// [`Grid::set_view`], callers should follow this with a full redraw.
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    pub fn shows_awake(&self) -> bool {
+        self.show_awake
+    }
+
+    /// Tints the cells that will be updated next tick, empty or not, when
+    /// the grid is drawn with [`Grid::draw`], for checking that settled
+    /// areas really do fall asleep. The awake cells change every tick, so
+    /// callers should redraw in full while this is on; [`Grid::draw_dirty`]
+    /// leaves the overlay out.
+    pub fn set_show_awake(&mut self, show: bool) {
+        self.show_awake = show;
+    }
+
+    /// Draws every visible particle onto a freshly cleared `renderer`.
+    pub fn draw(&self, renderer: &mut dyn Renderer) -> Result<(), String> {
+        let mut cells: Vec<_> = self.iter_filled()
+            .filter(|&(point, _)| renderer.is_visible(point))
+            .filter_map(|(point, _)| self.color_at(point).map(|color| (point, color)))
+            .collect();
+        if self.show_awake {
+            self.tint_awake(renderer, &mut cells);
+        }
+
+        renderer.clear()?;
+        renderer.fill_cells(&cells)?;
+        renderer.present()
+    }
+
+    /// Redraws only the cells changed since [`Grid::clear_dirty`], onto a
+    /// `renderer` still showing the frame before.
+    pub fn draw_dirty(&self, renderer: &mut dyn Renderer) -> Result<(), String> {
+        let mut filled = Vec::new();
+        let mut emptied = Vec::new();
+        for &point in self.dirty().iter().filter(|&&point| renderer.is_visible(point)) {
+            match self.color_at(point) {
+                Some(color) => filled.push((point, color)),
+                None => emptied.push(point),
+            }
+        }
+
+        renderer.fill_cells(&filled)?;
+        renderer.clear_cells(&emptied)?;
+        renderer.present()
+    }
+
+    /// Mixes `AWAKE_TINT` into the colors of awake particles among `cells`
+    /// and adds awake empty cells in the tint alone.
+    fn tint_awake(&self, renderer: &dyn Renderer, cells: &mut Vec<(Point, Color)>) {
+        for (point, color) in cells.iter_mut() {
+            let idx = self.index_of(*point).expect("drawn cells are on the grid");
+            if self.awake.flags[idx] {
+                *color = color.mixed(AWAKE_TINT, AWAKE_OPACITY);
+            }
+        }
+
+        let empty = Color::default().mixed(AWAKE_TINT, AWAKE_OPACITY);
+        for (&idx, &point) in self.awake.indices.iter().zip(&self.awake.points) {
+            if self.data[idx].is_none() && renderer.is_visible(point) {
+                cells.push((point, empty));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Particle,
+        Sand,
+        Stone,
+    };
+
+    /// Remembers what was drawn where, for checking against the grid.
+    #[derive(Default)]
+    struct Recorder {
+        filled: Vec<(Point, Color)>,
+        cleared: Vec<Point>,
+        frames: usize,
+    }
+
+    impl Renderer for Recorder {
+        fn clear(&mut self) -> Result<(), String> {
+            self.filled.clear();
+            Ok(())
+        }
+
+        fn fill_cells(&mut self, cells: &[(Point, Color)]) -> Result<(), String> {
+            self.filled.extend_from_slice(cells);
+            Ok(())
+        }
+
+        fn clear_cells(&mut self, cells: &[Point]) -> Result<(), String> {
+            self.cleared.extend_from_slice(cells);
+            Ok(())
+        }
+
+        fn present(&mut self) -> Result<(), String> {
+            self.frames += 1;
+            Ok(())
+        }
+
+        fn is_visible(&self, point: Point) -> bool {
+            point.x < 2
+        }
+    }
+
+    #[test]
+    fn draws_visible_particles_only() {
+        let mut grid = Grid::new(4, 1);
+        grid[Point::new(1, 0)] = Some(Particle::Stone(Stone));
+        grid[Point::new(3, 0)] = Some(Particle::Stone(Stone));
+        let mut recorder = Recorder::default();
+
+        grid.draw(&mut recorder).unwrap();
+
+        assert_eq!(recorder.filled, [(Point::new(1, 0), grid.color_at(Point::new(1, 0)).unwrap())]);
+        assert_eq!(recorder.frames, 1);
+    }
+
+    #[test]
+    fn redraws_changed_cells() {
+        let mut grid = Grid::new(2, 1);
+        grid[Point::new(0, 0)] = Some(Particle::Stone(Stone));
+        grid.clear_dirty();
+        grid[Point::new(0, 0)] = None;
+        grid[Point::new(1, 0)] = Some(Particle::Stone(Stone));
+        let mut recorder = Recorder::default();
+
+        grid.draw_dirty(&mut recorder).unwrap();
+
+        assert_eq!(recorder.cleared, [Point::new(0, 0)]);
+        assert_eq!(recorder.filled, [(Point::new(1, 0), grid.color_at(Point::new(1, 0)).unwrap())]);
+    }
+
+    #[test]
+    fn highlights_awake_cells_only_when_asked() {
+        let mut grid = Grid::new(2, 3);
+        grid[Point::new(0, 2)] = Some(Particle::Stone(Stone));
+        grid.run(5);
+        grid[Point::new(1, 0)] = Some(Particle::Sand(Sand));
+        let mut plain = Recorder::default();
+        grid.draw(&mut plain).unwrap();
+
+        grid.set_show_awake(true);
+        let mut overlaid = Recorder::default();
+        grid.draw(&mut overlaid).unwrap();
+
+        // The sleeping stone is drawn as usual and the new grain tinted,
+        // along with the empty cells it woke.
+        let sand = Point::new(1, 0);
+        let stone = Point::new(0, 2);
+        let color = |recorder: &Recorder, point| recorder.filled.iter()
+            .find(|&&(drawn, _)| drawn == point)
+            .map(|&(_, color)| color);
+        assert_eq!(color(&overlaid, stone), color(&plain, stone));
+        assert_eq!(color(&overlaid, sand), Some(grid.color_at(sand).unwrap().mixed(AWAKE_TINT, AWAKE_OPACITY)));
+        assert!(color(&overlaid, Point::new(0, 0)).is_some());
+        assert!(color(&plain, Point::new(0, 0)).is_none());
+    }
+
+    #[test]
+    fn temperature_view_colors_by_heat_alone() {
+        let mut grid = Grid::new(3, 1);
+        grid[Point::new(0, 0)] = Some(Particle::Stone(Stone));
+        grid[Point::new(1, 0)] = Some(Particle::Sand(Sand));
+        grid[Point::new(2, 0)] = Some(Particle::Stone(Stone));
+        grid.set_temperature(Point::new(2, 0), 500.0);
+
+        grid.set_view(View::Temperature);
+
+        let colors: Vec<_> = (0 .. 3).map(|x| grid.color_at(Point::new(x, 0)).unwrap()).collect();
+        assert_eq!(colors[0], colors[1]);
+        assert!(colors[2].r > colors[0].r && colors[2].b < colors[0].b);
+    }
+}
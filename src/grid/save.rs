@@ -0,0 +1,160 @@
+use std::{
+    fs::File,
+    io::{
+        self,
+        BufReader,
+        BufWriter,
+        Read,
+        Write,
+    },
+    path::Path,
+};
+
+use crate::{
+    material,
+    Grid,
+};
+
+/// Save files start with this tag, followed by a format version.
+const MAGIC: &[u8; 4] = b"ITBG";
+const VERSION: u8 = 1;
+
+/// Id written for empty cells.
+const EMPTY: u16 = 0;
+
+impl Grid {
+    /// Writes the grid's dimensions and the [`Material::id`] of every cell
+    /// to `path`, in a little-endian binary format.
+    ///
+    /// [`Material::id`]: crate::Material::id
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        writer.write_all(&(self.width as u32).to_le_bytes())?;
+        writer.write_all(&(self.height as u32).to_le_bytes())?;
+
+        for cell in self.data.iter() {
+            let id = cell.as_ref().map_or(EMPTY, |material| material.id());
+            writer.write_all(&id.to_le_bytes())?;
+        }
+
+        writer.flush()
+    }
+
+    /// Reads a grid back from a file written by [`Grid::save`].
+    pub fn load(path: &Path) -> io::Result<Grid> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        let mut version = [0; 1];
+        reader.read_exact(&mut version)?;
+        if &magic != MAGIC || version[0] != VERSION {
+            return Err(invalid_data("not a grid save file"));
+        }
+
+        let width = read_u32(&mut reader)? as usize;
+        let height = read_u32(&mut reader)? as usize;
+        let mut grid = Grid::try_new(width, height)
+            .map_err(|error| invalid_data(&error.to_string()))?;
+
+        for idx in 0 .. grid.data.len() {
+            let mut id = [0; 2];
+            reader.read_exact(&mut id)?;
+
+            match u16::from_le_bytes(id) {
+                EMPTY => {},
+                id => grid.place(idx, material::from_id(id)
+                    .ok_or_else(|| invalid_data("unknown material id"))?),
+            }
+        }
+
+        grid.wake_all();
+        Ok(grid)
+    }
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env,
+        fs,
+        path::PathBuf,
+    };
+
+    use super::*;
+    use crate::{
+        Particle,
+        Point,
+        Sand,
+        Stone,
+        Water,
+    };
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("into_the_breach-{}-{}", std::process::id(), name))
+    }
+
+    fn ids(grid: &Grid) -> Vec<u16> {
+        grid.data.iter().map(|cell| cell.as_ref().map_or(EMPTY, |m| m.id())).collect()
+    }
+
+    fn round_trip(grid: &Grid, name: &str) -> Grid {
+        let path = temp_path(name);
+        grid.save(&path).unwrap();
+        let loaded = Grid::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        loaded
+    }
+
+    #[test]
+    fn empty_grid_round_trips() {
+        let grid = Grid::new(7, 5);
+        let loaded = round_trip(&grid, "empty");
+
+        assert_eq!((loaded.width(), loaded.height()), (7, 5));
+        assert_eq!(ids(&loaded), ids(&grid));
+    }
+
+    #[test]
+    fn full_grid_round_trips() {
+        let mut grid = Grid::new(6, 4);
+        for y in 0 .. 4 {
+            for x in 0 .. 6 {
+                let material: Particle = match (x + y) % 3 {
+                    0 => Particle::Sand(Sand),
+                    1 => Particle::Water(Water),
+                    _ => Particle::Stone(Stone),
+                };
+                grid[Point::new(x, y)] = Some(material);
+            }
+        }
+
+        let loaded = round_trip(&grid, "full");
+
+        assert_eq!(ids(&loaded), ids(&grid));
+    }
+
+    #[test]
+    fn load_rejects_other_files() {
+        let path = temp_path("garbage");
+        fs::write(&path, b"not a save").unwrap();
+        let error = Grid::load(&path).err().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
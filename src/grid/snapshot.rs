@@ -0,0 +1,125 @@
+use rand::rngs::StdRng;
+
+use crate::Grid;
+
+use super::{
+    CellSet,
+    Cells,
+};
+
+/// Everything about a grid's particles at one moment, taken by
+/// [`Grid::snapshot`] and put back by [`Grid::restore`]. Lives in memory
+/// only; see [`Grid::save`] for keeping a grid across runs.
+#[derive(Clone)]
+pub struct GridSnapshot {
+    data: Cells,
+    shade: Vec<i8>,
+    temperature: Vec<f32>,
+    velocity: Vec<f32>,
+    charge: Vec<u8>,
+    electrified: Vec<bool>,
+    awake: CellSet,
+    unsettled: CellSet,
+    charged: CellSet,
+    width: usize,
+    height: usize,
+    rng: StdRng,
+}
+
+impl Grid {
+    /// Copies the state of every cell, along with which are awake or
+    /// unsettled and the random number generator, so that restoring the
+    /// snapshot replays the same future.
+    pub fn snapshot(&self) -> GridSnapshot {
+        GridSnapshot {
+            data: self.data.clone(),
+            shade: self.shade.clone(),
+            temperature: self.temperature.clone(),
+            velocity: self.velocity.clone(),
+            charge: self.charge.clone(),
+            electrified: self.electrified.clone(),
+            awake: self.awake.clone(),
+            unsettled: self.unsettled.clone(),
+            charged: self.charged.clone(),
+            width: self.width,
+            height: self.height,
+            rng: self.rng.clone(),
+        }
+    }
+
+    /// Puts every cell back as it was when `snapshot` was taken, resizing
+    /// the grid if it has changed size since. The cells awake then are woken
+    /// again and the warm cells found afresh, so the simulation carries on
+    /// exactly as it did from there. Nothing is marked dirty, so callers
+    /// rendering incrementally should follow this with a full redraw.
+    pub fn restore(&mut self, snapshot: &GridSnapshot) {
+        self.data.clone_from(&snapshot.data);
+        self.shade.clone_from(&snapshot.shade);
+        self.temperature.clone_from(&snapshot.temperature);
+        self.velocity.clone_from(&snapshot.velocity);
+        self.charge.clone_from(&snapshot.charge);
+        self.electrified.clone_from(&snapshot.electrified);
+        self.awake.clone_from(&snapshot.awake);
+        self.unsettled.clone_from(&snapshot.unsettled);
+        self.charged.clone_from(&snapshot.charged);
+        self.rng = snapshot.rng.clone();
+
+        let len = self.data.len();
+        if snapshot.width != self.width || snapshot.height != self.height {
+            self.width = snapshot.width;
+            self.height = snapshot.height;
+            self.claimed = vec![false; len];
+            self.dirty = CellSet::new(len);
+        }
+        self.dirty.clear();
+        self.warm = CellSet::new(len);
+        for idx in 0 .. len {
+            self.track_heat(idx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Particle,
+        Point,
+        Sand,
+    };
+
+    use super::*;
+
+    #[test]
+    fn restoring_replays_the_same_future() {
+        let mut grid = Grid::new(8, 8);
+        for x in 0 .. 8 {
+            grid[Point::new(x, 0)] = Some(Particle::Sand(Sand));
+        }
+        grid.set_temperature(Point::new(3, 0), 80.0);
+        grid.run(2);
+
+        let snapshot = grid.snapshot();
+        grid.run(20);
+        let first = grid.to_rgb_buffer();
+
+        grid.clear();
+        grid.restore(&snapshot);
+        grid.run(20);
+
+        assert_eq!(grid.to_rgb_buffer(), first);
+    }
+
+    #[test]
+    fn restoring_undoes_a_resize() {
+        let mut grid = Grid::new(4, 4);
+        grid[Point::new(3, 3)] = Some(Particle::Sand(Sand));
+        let snapshot = grid.snapshot();
+
+        grid.resize(2, 2).unwrap();
+        grid.restore(&snapshot);
+
+        assert_eq!((grid.width(), grid.height()), (4, 4));
+        assert!(grid[Point::new(3, 3)].is_some());
+        grid.update();
+    }
+}
@@ -0,0 +1,138 @@
+use rand::Rng;
+
+use crate::{
+    Grid,
+    Material,
+    Particle,
+    Point,
+    Rect,
+};
+
+use super::Mode;
+
+/// A patch of cells that keeps producing a material on its own, such as a
+/// row of sky raining water or the neck of an hourglass running sand. Added
+/// with [`Grid::add_spawner`].
+#[derive(Clone)]
+pub struct Spawner {
+    /// The cells particles appear in, often a single row or cell.
+    pub area: Rect,
+    pub material: Particle,
+    /// Chance each tick, from `0.0` to `1.0`, that each empty cell of the
+    /// area gets a new particle.
+    pub rate: f32,
+}
+
+impl Spawner {
+    pub fn new(area: Rect, material: &dyn Material, rate: f32) -> Spawner {
+        Spawner { area, material: material.particle(), rate }
+    }
+
+    /// Whether any of the spawner's cells lie within `radius` of `center`.
+    fn is_within(&self, center: Point, radius: i32) -> bool {
+        let (x, y) = (self.area.x, self.area.y);
+        let nearest = Point::new(
+            center.x.clamp(x, x + self.area.width as i32 - 1),
+            center.y.clamp(y, y + self.area.height as i32 - 1),
+        );
+        let (dx, dy) = (nearest.x - center.x, nearest.y - center.y);
+        dx * dx + dy * dy <= radius * radius
+    }
+}
+
+impl Grid {
+    pub fn spawners(&self) -> &[Spawner] {
+        &self.spawners
+    }
+
+    /// Starts `spawner` emitting, from the next tick on.
+    pub fn add_spawner(&mut self, spawner: Spawner) {
+        self.spawners.push(spawner);
+    }
+
+    /// Stops every spawner with a cell within `radius` of `center`, as the
+    /// eraser passes over it, returning how many there were.
+    pub fn remove_spawners(&mut self, center: Point, radius: i32) -> usize {
+        let before = self.spawners.len();
+        self.spawners.retain(|spawner| !spawner.is_within(center, radius));
+        before - self.spawners.len()
+    }
+
+    /// Fills the empty cells under each spawner at its rate. Runs before the
+    /// awake cells are gathered, so new particles start moving the tick they
+    /// appear and leave room for the next ones.
+    pub(super) fn emit(&mut self) {
+        if self.mode == Mode::Life { return; }
+
+        let mut spawned = Vec::new();
+        for spawner in &self.spawners {
+            for point in spawner.area.points() {
+                let idx = match self.index_of(point) {
+                    Some(idx) if self.data[idx].is_none() => idx,
+                    _ => continue,
+                };
+                if spawner.rate < 1.0 && self.rng.gen::<f32>() >= spawner.rate { continue; }
+
+                spawned.push((idx, spawner.material.particle()));
+            }
+        }
+
+        for (idx, material) in spawned {
+            self.place(idx, material);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Sand,
+        Water,
+    };
+
+    use super::*;
+
+    #[test]
+    fn emits_whenever_its_cell_is_free_at_full_rate() {
+        let mut grid = Grid::new(1, 40);
+        let spout = Point::new(0, 0);
+        grid.add_spawner(Spawner::new(Rect::new(spout.x, spout.y, 1, 1), &Sand, 1.0));
+
+        let mut free = 0;
+        for _ in 0 .. 20 {
+            if grid[spout].is_none() { free += 1; }
+            grid.update();
+        }
+
+        // Each grain waits a tick for the one before to clear the way, so
+        // the stream comes out every other tick or so.
+        assert_eq!(grid.count_filled(), free);
+        assert!(free >= 10, "{} grains", free);
+    }
+
+    #[test]
+    fn emits_at_its_rate() {
+        let mut grid = Grid::new(100, 40);
+        grid.add_spawner(Spawner::new(Rect::new(0, 0, 100, 1), &Water, 0.1));
+
+        grid.run(20);
+
+        // About ten drops a tick, out of the row of a hundred.
+        let count = grid.count_filled();
+        assert!(count > 150 && count < 250, "{} drops", count);
+    }
+
+    #[test]
+    fn erasing_near_a_spawner_removes_it() {
+        let mut grid = Grid::new(10, 10);
+        grid.add_spawner(Spawner::new(Rect::new(0, 0, 10, 1), &Water, 1.0));
+        grid.add_spawner(Spawner::new(Rect::new(5, 9, 1, 1), &Sand, 1.0));
+
+        assert_eq!(grid.remove_spawners(Point::new(3, 3), 2), 0);
+        assert_eq!(grid.remove_spawners(Point::new(3, 3), 3), 1);
+        assert_eq!(grid.spawners().len(), 1);
+
+        grid.update();
+        assert_eq!(grid.count_filled(), 1);
+    }
+}
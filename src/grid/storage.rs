@@ -0,0 +1,266 @@
+use std::{
+    iter,
+    mem,
+    ops::{
+        Index,
+        IndexMut,
+    },
+};
+
+use crate::{
+    Cell,
+    Material,
+    Particle,
+};
+
+/// Cells per run of [`Storage::Sparse`] storage, a quarter of a row of a
+/// 4000 cell wide grid.
+const RUN: usize = 1024;
+
+/// What an unallocated run of sparse storage reads as.
+static EMPTY: Cell = None;
+
+/// How a grid keeps its cells, chosen when it is made with
+/// [`Grid::with_storage`](crate::Grid::with_storage). The grid behaves the
+/// same either way; only the memory it takes and how fast it runs differ.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Storage {
+    /// Every cell in one block, the fastest to read and write.
+    Dense,
+    /// Runs of cells allocated only once something is put in them, for
+    /// huge grids that are mostly empty. A run, once allocated, is kept
+    /// until the grid is cleared. The grid's other per-cell state, such as
+    /// temperatures, is stored in full either way.
+    Sparse,
+}
+
+/// A grid's cells, indexed in scan order, laid out as one of the
+/// [`Storage`] kinds.
+#[derive(Clone)]
+pub(super) enum Cells {
+    Dense(Vec<Cell>),
+    Sparse {
+        /// Each run of `RUN` cells, or fewer for the last, if allocated.
+        runs: Vec<Option<Box<[Cell]>>>,
+        len: usize,
+    },
+}
+
+impl Default for Cells {
+    fn default() -> Cells {
+        Cells::Dense(Vec::new())
+    }
+}
+
+impl Cells {
+    /// `len` empty cells.
+    pub(super) fn new(storage: Storage, len: usize) -> Cells {
+        match storage {
+            Storage::Dense => Cells::Dense(iter::repeat_with(|| None).take(len).collect()),
+            Storage::Sparse => Cells::Sparse { runs: vec![None; len.div_ceil(RUN)], len },
+        }
+    }
+
+    /// `len` cells filled from `cells` in scan order.
+    pub(super) fn from_cells(storage: Storage, len: usize, cells: impl Iterator<Item = Cell>) -> Cells {
+        match storage {
+            Storage::Dense => Cells::Dense(cells.collect()),
+            Storage::Sparse => {
+                let mut sparse = Cells::new(storage, len);
+                for (idx, cell) in cells.enumerate() {
+                    sparse.set(idx, cell);
+                }
+                sparse
+            },
+        }
+    }
+
+    pub(super) fn storage(&self) -> Storage {
+        match self {
+            Cells::Dense(_) => Storage::Dense,
+            Cells::Sparse { .. } => Storage::Sparse,
+        }
+    }
+
+    pub(super) fn len(&self) -> usize {
+        match self {
+            Cells::Dense(cells) => cells.len(),
+            Cells::Sparse { len, .. } => *len,
+        }
+    }
+
+    pub(super) fn get(&self, idx: usize) -> Option<&Cell> {
+        if idx < self.len() { Some(&self[idx]) } else { None }
+    }
+
+    pub(super) fn get_mut(&mut self, idx: usize) -> Option<&mut Cell> {
+        if idx < self.len() { Some(&mut self[idx]) } else { None }
+    }
+
+    /// The particle at `idx` to change in place, if there is one, without
+    /// allocating sparse storage for an empty cell.
+    pub(super) fn material_mut(&mut self, idx: usize) -> Option<&mut Particle> {
+        self[idx].as_ref()?;
+        self[idx].as_mut()
+    }
+
+    /// Puts `cell` at `idx`. Emptying a cell that is already empty leaves
+    /// sparse storage as it was, rather than allocating its run.
+    pub(super) fn set(&mut self, idx: usize, cell: Cell) {
+        if cell.is_none() && self[idx].is_none() { return; }
+
+        self[idx] = cell;
+    }
+
+    /// Empties the cell at `idx`, returning what it held.
+    pub(super) fn take(&mut self, idx: usize) -> Cell {
+        self[idx].as_ref()?;
+        self[idx].take()
+    }
+
+    pub(super) fn swap(&mut self, a: usize, b: usize) {
+        match self {
+            Cells::Dense(cells) => cells.swap(a, b),
+            Cells::Sparse { .. } => {
+                let (from_a, from_b) = (self.take(a), self.take(b));
+                self.set(a, from_b);
+                self.set(b, from_a);
+            },
+        }
+    }
+
+    /// Empties every cell, freeing any runs of sparse storage.
+    pub(super) fn clear(&mut self) {
+        match self {
+            Cells::Dense(cells) => cells.iter_mut().for_each(|cell| *cell = None),
+            Cells::Sparse { runs, .. } => runs.iter_mut().for_each(|run| *run = None),
+        }
+    }
+
+    /// Every cell in scan order.
+    pub(super) fn iter(&self) -> impl Iterator<Item = &Cell> {
+        (0 .. self.len()).map(move |idx| &self[idx])
+    }
+
+    /// Every particle with its index, in scan order, skipping unallocated
+    /// runs of sparse storage outright.
+    pub(super) fn filled(&self) -> Box<dyn Iterator<Item = (usize, &dyn Material)> + '_> {
+        match self {
+            Cells::Dense(cells) => Box::new(cells.iter()
+                .enumerate()
+                .filter_map(|(idx, cell)| cell.as_deref().map(|material| (idx, material)))),
+            Cells::Sparse { runs, .. } => Box::new(runs.iter()
+                .enumerate()
+                .filter_map(|(number, run)| run.as_ref().map(|run| (number * RUN, run)))
+                .flat_map(|(start, run)| run.iter()
+                    .enumerate()
+                    .filter_map(move |(offset, cell)| {
+                        cell.as_deref().map(|material| (start + offset, material))
+                    }))),
+        }
+    }
+
+    /// Bytes the cells take up, particles included.
+    pub(super) fn bytes(&self) -> usize {
+        match self {
+            Cells::Dense(cells) => cells.len() * mem::size_of::<Cell>(),
+            Cells::Sparse { runs, .. } => {
+                let allocated: usize = runs.iter().flatten().map(|run| run.len()).sum();
+                runs.len() * mem::size_of::<Option<Box<[Cell]>>>() + allocated * mem::size_of::<Cell>()
+            },
+        }
+    }
+}
+
+impl Index<usize> for Cells {
+    type Output = Cell;
+
+    fn index(&self, idx: usize) -> &Cell {
+        match self {
+            Cells::Dense(cells) => &cells[idx],
+            Cells::Sparse { runs, .. } => runs[idx / RUN].as_ref().map_or(&EMPTY, |run| &run[idx % RUN]),
+        }
+    }
+}
+
+/// Writing to a cell of sparse storage allocates its run, if it hasn't
+/// been already.
+impl IndexMut<usize> for Cells {
+    fn index_mut(&mut self, idx: usize) -> &mut Cell {
+        match self {
+            Cells::Dense(cells) => &mut cells[idx],
+            Cells::Sparse { runs, len } => {
+                let number = idx / RUN;
+                let run_len = RUN.min(*len - number * RUN);
+                let run = runs[number].get_or_insert_with(|| {
+                    iter::repeat_with(|| None).take(run_len).collect()
+                });
+                &mut run[idx % RUN]
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Grid,
+        Point,
+        Rect,
+        Sand,
+        Stone,
+        Water,
+    };
+
+    #[test]
+    fn sparse_storage_only_allocates_what_is_written() {
+        let mut cells = Cells::new(Storage::Sparse, 10 * RUN + 5);
+        let empty = cells.bytes();
+
+        cells.set(3, None);
+        assert_eq!(cells.bytes(), empty);
+        cells.set(4 * RUN, Some(Particle::Sand(Sand)));
+        assert_eq!(cells.bytes(), empty + RUN * mem::size_of::<Cell>());
+
+        // The short last run holds just the cells past the last whole one.
+        cells.set(10 * RUN + 4, Some(Particle::Sand(Sand)));
+        assert_eq!(cells.bytes(), empty + (RUN + 5) * mem::size_of::<Cell>());
+        assert!(cells.get(10 * RUN + 5).is_none());
+
+        cells.swap(4 * RUN, 2 * RUN);
+        let filled: Vec<_> = cells.filled().map(|(idx, _)| idx).collect();
+        assert_eq!(filled, [2 * RUN, 10 * RUN + 4]);
+    }
+
+    #[test]
+    fn sparse_grids_play_out_like_dense_ones() {
+        // Everything happens in the top sixth of a tall grid.
+        let scenario = |storage| {
+            let mut grid = Grid::with_storage(64, 256, 7, storage);
+            grid.fill_rect(Rect::new(0, 40, 64, 2), &Stone);
+            grid.paint_circle(Point::new(20, 10), 6, &Sand);
+            grid.paint_circle(Point::new(44, 10), 6, &Water);
+            grid.run(200);
+            grid.flip_horizontal();
+            grid.resize(60, 256).unwrap();
+            grid.run(50);
+            grid
+        };
+        let (dense, sparse) = (scenario(Storage::Dense), scenario(Storage::Sparse));
+
+        assert_eq!(sparse.storage(), Storage::Sparse);
+        assert_eq!(sparse.state_hash(), dense.state_hash());
+        assert_eq!(sparse.count_filled(), dense.count_filled());
+        assert!(
+            sparse.storage_bytes() * 4 < dense.storage_bytes(),
+            "{} bytes sparse, {} dense", sparse.storage_bytes(), dense.storage_bytes(),
+        );
+        // The rest of the grid's per-cell state is dense either way, and
+        // outweighs the cells.
+        assert!(
+            sparse.memory_bytes() < dense.memory_bytes(),
+            "{} bytes in all sparse, {} dense", sparse.memory_bytes(), dense.memory_bytes(),
+        );
+    }
+}
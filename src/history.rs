@@ -0,0 +1,249 @@
+use std::{
+    collections::{
+        HashSet,
+        VecDeque,
+    },
+    mem,
+};
+
+use crate::{
+    grid,
+    BrushShape,
+    Cell,
+    Grid,
+    Material,
+    Point,
+    Rect,
+};
+
+/// The cells one edit changed, each with the contents it had before.
+#[derive(Default)]
+struct Edit {
+    cells: Vec<(Point, Cell)>,
+    recorded: HashSet<Point>,
+}
+
+impl Edit {
+    /// Remembers what is at `point` now, unless this edit already has.
+    fn record(&mut self, grid: &Grid, point: Point) {
+        if let Some(cell) = grid.get(point) {
+            if self.recorded.insert(point) {
+                self.cells.push((point, *cell));
+            }
+        }
+    }
+
+    /// Puts every recorded cell back, keeping what was there instead so the
+    /// edit can be reapplied the same way.
+    fn revert(&mut self, grid: &mut Grid) {
+        for (point, cell) in self.cells.iter_mut().rev() {
+            *cell = mem::replace(&mut grid[*point], cell.take());
+        }
+    }
+}
+
+/// Undo and redo for edits made to a grid by hand. Only the cells each edit
+/// touches are kept, not copies of the grid, and only the most recent
+/// `depth` edits can be undone.
+///
+/// Particles keep moving after they're painted, so undoing is most
+/// predictable while the simulation is paused: it puts back whatever was in
+/// the painted cells, wherever the paint has since gone.
+pub struct History {
+    undo: VecDeque<Edit>,
+    redo: Vec<Edit>,
+    /// The edit being built up, such as a brush stroke over several frames.
+    current: Option<Edit>,
+    depth: usize,
+}
+
+impl History {
+    pub fn new(depth: usize) -> History {
+        History {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            current: None,
+            depth,
+        }
+    }
+
+    /// Like [`Grid::paint_circle`], adding to the current edit.
+    pub fn paint_circle(
+        &mut self,
+        grid: &mut Grid,
+        center: Point,
+        radius: i32,
+        material: &dyn Material,
+    ) {
+        self.record(grid, grid::circle(center, radius));
+        grid.paint_circle(center, radius, material);
+    }
+
+    /// Like [`Grid::paint_line`], adding to the current edit.
+    pub fn paint_line(
+        &mut self,
+        grid: &mut Grid,
+        from: Point,
+        to: Point,
+        radius: i32,
+        shape: BrushShape,
+        material: &dyn Material,
+    ) {
+        self.record(grid, grid::line(from, to).flat_map(|center| shape.area(center, radius)));
+        grid.paint_line(from, to, radius, shape, material);
+    }
+
+    /// Like [`Grid::erase_line`], adding to the current edit.
+    pub fn erase_line(&mut self, grid: &mut Grid, from: Point, to: Point, radius: i32) {
+        self.record(grid, grid::line(from, to).flat_map(|center| grid::circle(center, radius)));
+        grid.erase_line(from, to, radius);
+    }
+
+    /// Like [`Grid::fill_rect`], adding to the current edit.
+    pub fn fill_rect(&mut self, grid: &mut Grid, rect: Rect, material: &dyn Material) {
+        self.record(grid, rect.points());
+        grid.fill_rect(rect, material);
+    }
+
+    /// Like [`Grid::paste_region`], adding to the current edit.
+    pub fn paste_region(
+        &mut self,
+        grid: &mut Grid,
+        at: Point,
+        buffer: &[Cell],
+        width: u32,
+        height: u32,
+    ) {
+        self.record(grid, Rect::new(at.x, at.y, width, height).points());
+        grid.paste_region(at, buffer, width, height);
+    }
+
+    /// Like [`Grid::erase_circle`], adding to the current edit.
+    pub fn erase_circle(&mut self, grid: &mut Grid, center: Point, radius: i32) {
+        self.record(grid, grid::circle(center, radius));
+        grid.erase_circle(center, radius);
+    }
+
+    /// Like [`Grid::clear`], as an edit of its own.
+    pub fn clear(&mut self, grid: &mut Grid) {
+        self.finish();
+        let filled: Vec<_> = grid.iter_filled().map(|(point, _)| point).collect();
+        self.record(grid, filled);
+        grid.clear();
+        self.finish();
+    }
+
+    /// Ends the current edit, so that later changes are undone separately.
+    pub fn finish(&mut self) {
+        if let Some(edit) = self.current.take() {
+            if edit.cells.is_empty() { return; }
+
+            self.undo.push_back(edit);
+            if self.undo.len() > self.depth {
+                self.undo.pop_front();
+            }
+            self.redo.clear();
+        }
+    }
+
+    /// Reverts the most recent edit. Returns whether there was one.
+    pub fn undo(&mut self, grid: &mut Grid) -> bool {
+        self.finish();
+        match self.undo.pop_back() {
+            Some(mut edit) => {
+                edit.revert(grid);
+                self.redo.push(edit);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Reapplies the most recently undone edit. Returns whether there was
+    /// one.
+    pub fn redo(&mut self, grid: &mut Grid) -> bool {
+        self.finish();
+        match self.redo.pop() {
+            Some(mut edit) => {
+                edit.revert(grid);
+                self.undo.push_back(edit);
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn record(&mut self, grid: &Grid, points: impl IntoIterator<Item = Point>) {
+        let edit = self.current.get_or_insert_with(Edit::default);
+        for point in points {
+            edit.record(grid, point);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Sand,
+        Stone,
+    };
+
+    fn ids(grid: &Grid) -> Vec<(Point, u16)> {
+        grid.iter_filled().map(|(point, material)| (point, material.id())).collect()
+    }
+
+    #[test]
+    fn undoes_and_redoes_strokes() {
+        let mut grid = Grid::new(8, 8);
+        let mut history = History::new(50);
+        history.paint_circle(&mut grid, Point::new(2, 2), 1, &Stone);
+        history.finish();
+        let painted = ids(&grid);
+
+        // One stroke painted over several frames.
+        history.paint_circle(&mut grid, Point::new(2, 2), 1, &Sand);
+        history.paint_circle(&mut grid, Point::new(3, 2), 1, &Sand);
+        history.finish();
+
+        assert!(history.undo(&mut grid));
+        assert_eq!(ids(&grid), painted);
+
+        assert!(history.redo(&mut grid));
+        assert!(ids(&grid).iter().all(|&(_, id)| id == Sand.id()));
+
+        assert!(history.undo(&mut grid));
+        assert!(history.undo(&mut grid));
+        assert_eq!(grid.count_filled(), 0);
+        assert!(!history.undo(&mut grid));
+    }
+
+    #[test]
+    fn undoes_a_clear() {
+        let mut grid = Grid::new(8, 8);
+        let mut history = History::new(50);
+        history.paint_circle(&mut grid, Point::new(4, 4), 2, &Stone);
+        let painted = ids(&grid);
+
+        history.clear(&mut grid);
+        assert_eq!(grid.count_filled(), 0);
+
+        history.undo(&mut grid);
+        assert_eq!(ids(&grid), painted);
+    }
+
+    #[test]
+    fn forgets_edits_past_its_depth() {
+        let mut grid = Grid::new(8, 8);
+        let mut history = History::new(2);
+        for x in 0 .. 3 {
+            history.paint_circle(&mut grid, Point::new(x, 0), 0, &Stone);
+            history.finish();
+        }
+
+        assert!(history.undo(&mut grid));
+        assert!(history.undo(&mut grid));
+        assert!(!history.undo(&mut grid));
+        assert_eq!(ids(&grid), [(Point::new(0, 0), Stone.id())]);
+    }
+}
@@ -0,0 +1,164 @@
+use std::{
+    env,
+    path::Path,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{
+    Canvas,
+    Texture,
+    TextureCreator,
+};
+use sdl2::ttf::{
+    Font,
+    Sdl2TtfContext,
+};
+use sdl2::video::{
+    Window,
+    WindowContext,
+};
+
+use into_the_breach::{
+    BrushShape,
+    Grid,
+    Material,
+};
+
+/// Overrides the font the HUD is rendered with.
+const FONT_VAR: &str = "INTO_THE_BREACH_FONT";
+
+/// Fonts tried in order when `FONT_VAR` isn't set.
+const FONT_PATHS: &[&str] = &[
+    "/usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf",
+    "/usr/share/fonts/TTF/DejaVuSansMono.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSansMono.ttf",
+    "/System/Library/Fonts/Menlo.ttc",
+    "C:\\Windows\\Fonts\\consola.ttf",
+];
+
+const FONT_SIZE: u16 = 16;
+
+/// How often the figures are recalculated. Any faster and they jitter too
+/// much to read.
+const REFRESH: Duration = Duration::from_millis(500);
+
+/// How long a message passed to [`Hud::flash`] stays up.
+const FLASH: Duration = Duration::from_secs(2);
+
+const MARGIN: i32 = 8;
+
+const TEXT: Color = Color::RGB(255, 255, 255);
+
+/// Frame rate, particle count, simulation speed and the brush material and
+/// shape overlaid in the top-left corner, along with any message flashed up.
+/// Without a font the same text goes to the window title instead.
+pub struct Hud<'ttf, 'tex> {
+    font: Option<Font<'ttf, 'static>>,
+    texture: Option<Texture<'tex>>,
+    frames: u32,
+    since: Instant,
+    /// A message shown after the figures, and when it was first shown.
+    flash: Option<(String, Instant)>,
+    /// Set when the text must change before `REFRESH` is up.
+    stale: bool,
+    pub visible: bool,
+}
+
+impl<'ttf, 'tex> Hud<'ttf, 'tex> {
+    pub fn new(ttf: &'ttf Sdl2TtfContext) -> Hud<'ttf, 'tex> {
+        let font = match env::var(FONT_VAR) {
+            Ok(path) => ttf.load_font(&path, FONT_SIZE)
+                .map_err(|error| eprintln!("failed to load font {}: {}", path, error))
+                .ok(),
+            Err(_) => FONT_PATHS.iter()
+                .filter(|path| Path::new(path).exists())
+                .find_map(|path| ttf.load_font(path, FONT_SIZE).ok()),
+        };
+        if font.is_none() {
+            eprintln!("no font found, showing stats in the window title; set {} to use one", FONT_VAR);
+        }
+
+        Hud {
+            font,
+            texture: None,
+            frames: 0,
+            since: Instant::now(),
+            flash: None,
+            stale: false,
+            visible: true,
+        }
+    }
+
+    /// Shows `message` alongside the figures for a couple of seconds,
+    /// replacing any already up.
+    pub fn flash(&mut self, message: String) {
+        self.flash = Some((message, Instant::now()));
+        self.stale = true;
+    }
+
+    /// Counts a presented frame, refreshing the text once `REFRESH` has
+    /// passed or a flashed message comes or goes.
+    pub fn frame(
+        &mut self,
+        canvas: &mut Canvas<Window>,
+        texture_creator: &'tex TextureCreator<WindowContext>,
+        grid: &Grid,
+        speed: u32,
+        material: &dyn Material,
+        brush: BrushShape,
+    ) {
+        self.frames += 1;
+        if self.flash.as_ref().is_some_and(|(_, shown)| shown.elapsed() >= FLASH) {
+            self.flash = None;
+            self.stale = true;
+        }
+        let elapsed = self.since.elapsed();
+        if elapsed < REFRESH && !self.stale { return; }
+
+        let fps = self.frames as f64 / elapsed.as_secs_f64();
+        let mut text = format!(
+            "{:.0} fps  {} particles  x{}  {} {}",
+            fps, grid.count_filled(), speed, material.name(), brush.name(),
+        );
+        if let Some((message, _)) = &self.flash {
+            text.push_str("  ");
+            text.push_str(message);
+        }
+        self.frames = 0;
+        self.since = Instant::now();
+        self.stale = false;
+
+        match &self.font {
+            Some(font) => {
+                self.texture = font.render(&text)
+                    .blended(TEXT)
+                    .map_err(|error| error.to_string())
+                    .and_then(|surface| texture_creator
+                        .create_texture_from_surface(&surface)
+                        .map_err(|error| error.to_string()))
+                    .map_err(|error| eprintln!("failed to render HUD: {}", error))
+                    .ok();
+            },
+            None => {
+                let _ = canvas.window_mut().set_title(&text);
+            },
+        }
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>) -> Result<(), String> {
+        if !self.visible { return Ok(()); }
+
+        match &self.texture {
+            Some(texture) => {
+                let query = texture.query();
+                canvas.copy(texture, None, Rect::new(MARGIN, MARGIN, query.width, query.height))
+            },
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashSet;
+
+use sdl2::event::{
+    Event,
+    WindowEvent,
+};
+use sdl2::keyboard::{
+    Keycode,
+    Mod,
+};
+
+/// Which keys are held down and which modifiers are active, kept up to date
+/// from the event stream so the main loop can ask at any point in a frame,
+/// for chorded shortcuts and for anything that acts while a key is held.
+pub struct Input {
+    held: HashSet<Keycode>,
+    /// Keys pressed since the frame began, so a tap that is let go within a
+    /// frame is still seen as held for it.
+    pressed: HashSet<Keycode>,
+    keymod: Mod,
+}
+
+impl Input {
+    pub fn new() -> Input {
+        Input { held: HashSet::new(), pressed: HashSet::new(), keymod: Mod::NOMOD }
+    }
+
+    /// Takes in an event. Keys repeating while held are already held, so
+    /// repeats change nothing. The window losing focus releases everything,
+    /// since the keys let go of elsewhere are never heard about.
+    pub fn handle(&mut self, event: &Event) {
+        match *event {
+            Event::KeyDown { keycode, keymod, .. } => {
+                if let Some(keycode) = keycode {
+                    self.held.insert(keycode);
+                    self.pressed.insert(keycode);
+                }
+                self.keymod = keymod;
+            },
+            Event::KeyUp { keycode, keymod, .. } => {
+                if let Some(keycode) = keycode {
+                    self.held.remove(&keycode);
+                }
+                self.keymod = keymod;
+            },
+            Event::Window { win_event: WindowEvent::FocusLost, .. } => {
+                self.held.clear();
+                self.pressed.clear();
+                self.keymod = Mod::NOMOD;
+            },
+            _ => {},
+        }
+    }
+
+    /// Whether `keycode` is down, or was at any point this frame.
+    pub fn is_held(&self, keycode: Keycode) -> bool {
+        self.held.contains(&keycode) || self.pressed.contains(&keycode)
+    }
+
+    /// Forgets the keys pressed this frame, once the frame is done with
+    /// them.
+    pub fn end_frame(&mut self) {
+        self.pressed.clear();
+    }
+
+    pub fn ctrl(&self) -> bool {
+        self.keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD)
+    }
+
+    pub fn shift(&self) -> bool {
+        self.keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD)
+    }
+
+    pub fn alt(&self) -> bool {
+        self.keymod.intersects(Mod::LALTMOD | Mod::RALTMOD)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_down(keycode: Keycode, keymod: Mod, repeat: bool) -> Event {
+        Event::KeyDown { timestamp: 0, window_id: 0, keycode: Some(keycode), scancode: None, keymod, repeat }
+    }
+
+    fn key_up(keycode: Keycode, keymod: Mod) -> Event {
+        Event::KeyUp { timestamp: 0, window_id: 0, keycode: Some(keycode), scancode: None, keymod, repeat: false }
+    }
+
+    #[test]
+    fn tracks_held_keys_and_modifiers() {
+        let mut input = Input::new();
+
+        input.handle(&key_down(Keycode::LCtrl, Mod::LCTRLMOD, false));
+        input.handle(&key_down(Keycode::Z, Mod::LCTRLMOD, false));
+        assert!(input.is_held(Keycode::Z) && input.ctrl());
+        assert!(!input.shift() && !input.alt());
+
+        // Repeats while held change nothing.
+        input.handle(&key_down(Keycode::Z, Mod::LCTRLMOD, true));
+        input.handle(&key_up(Keycode::Z, Mod::LCTRLMOD));
+        input.end_frame();
+        assert!(!input.is_held(Keycode::Z));
+        assert!(input.ctrl());
+
+        input.handle(&key_up(Keycode::LCtrl, Mod::NOMOD));
+        assert!(!input.is_held(Keycode::LCtrl) && !input.ctrl());
+    }
+
+    #[test]
+    fn taps_within_a_frame_count_for_that_frame() {
+        let mut input = Input::new();
+        input.handle(&key_down(Keycode::Left, Mod::NOMOD, false));
+        input.handle(&key_up(Keycode::Left, Mod::NOMOD));
+        assert!(input.is_held(Keycode::Left));
+
+        input.end_frame();
+        assert!(!input.is_held(Keycode::Left));
+    }
+
+    #[test]
+    fn losing_focus_releases_everything() {
+        let mut input = Input::new();
+        input.handle(&key_down(Keycode::RShift, Mod::RSHIFTMOD, false));
+        input.handle(&key_down(Keycode::Escape, Mod::RSHIFTMOD, false));
+
+        input.handle(&Event::Window { timestamp: 0, window_id: 0, win_event: WindowEvent::FocusLost });
+
+        assert!(!input.is_held(Keycode::Escape));
+        assert!(!input.shift());
+    }
+}
@@ -0,0 +1,66 @@
+mod geometry;
+mod grid;
+mod history;
+mod material;
+mod palette;
+mod registry;
+#[cfg(feature = "web")]
+mod web;
+
+pub use geometry::{
+    Color,
+    Point,
+    Rect,
+};
+pub use grid::{
+    BoundaryMode,
+    BrushShape,
+    FrameSlot,
+    Grid,
+    GridError,
+    GridFrame,
+    GridSnapshot,
+    CHUNK_SIZE,
+    LevelError,
+    MAX_CELLS,
+    Mode,
+    Pattern,
+    Renderer,
+    Spawner,
+    Storage,
+    UpdateOrder,
+    View,
+};
+pub use history::History;
+pub use material::{
+    Acid,
+    Cell,
+    Crystal,
+    Drain,
+    Fire,
+    Glass,
+    Gunpowder,
+    Ice,
+    Lava,
+    Life,
+    Material,
+    Metal,
+    Mud,
+    Oil,
+    Particle,
+    Plant,
+    Sand,
+    Smoke,
+    Steam,
+    Stone,
+    Torch,
+    Water,
+    Wood,
+};
+pub use palette::{
+    Palette,
+    PaletteError,
+};
+pub use registry::MaterialRegistry;
+#[cfg(feature = "web")]
+pub use web::WebApp;
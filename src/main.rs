@@ -1,191 +1,697 @@
 use std::{
     collections::HashMap,
-    ops::{
-        Index,
-        IndexMut,
+    env,
+    error::Error,
+    fs,
+    path::Path,
+    process,
+    thread,
+    time::{
+        Duration,
+        Instant,
+        SystemTime,
+        UNIX_EPOCH,
     },
 };
 
 use sdl2::pixels::Color;
-use sdl2::rect::{
-    Point,
-    Rect,
-};
+use sdl2::rect::Rect;
 use sdl2::video::Window;
 use sdl2::render::Canvas;
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
-use std::time::Duration;
- 
-pub struct Grid {
-    data: Vec<Option<Box<Material>>>,
-    width: usize,
-    height: usize,
-}
+use sdl2::mouse::MouseButton;
 
-impl Grid {
-    pub fn new(width: usize, height: usize) -> Grid {
-        let mut data = Vec::new();
-        for _ in 0 .. width * height {
-            data.push(None);
-        }
+use into_the_breach::{
+    BoundaryMode,
+    BrushShape,
+    Cell,
+    Color as CellColor,
+    Grid,
+    History,
+    Life,
+    MaterialRegistry,
+    Mode,
+    Palette,
+    Particle,
+    Pattern,
+    Point,
+    Rect as CellRect,
+    Renderer,
+    Sand,
+    Spawner,
+    UpdateOrder,
+    View,
+};
 
-        Grid {
-            data,
-            width,
-            height
-        }
-    }
+mod camera;
+mod hud;
+mod input;
+mod options;
+mod picker;
+mod recording;
 
-    pub fn update(&mut self) {
-        let mut new_grid = Grid::new(self.width, self.height);
+use camera::Camera;
+use hud::Hud;
+use input::Input;
+use options::{
+    Options,
+    Present,
+};
+use picker::Picker;
+use recording::Recording;
 
-        for (idx, cell) in self.data.iter().enumerate() {
-            if let Some(material) = cell {
-                let x = (idx % self.width) as i32;
-                let y = (idx / self.width) as i32;
+const BRUSH_RADIUS: i32 = 3;
+const MAX_BRUSH_RADIUS: i32 = 64;
+/// How many cells a held arrow key scrolls the view each frame.
+const PAN_STEP: i32 = 4;
+/// The arrow keys and which way each scrolls the view.
+const PAN_KEYS: [(Keycode, i32, i32); 4] = [
+    (Keycode::Left, -1, 0),
+    (Keycode::Right, 1, 0),
+    (Keycode::Up, 0, -1),
+    (Keycode::Down, 0, 1),
+];
+/// Strength of the wind toggled with W.
+const WIND: f32 = 1.0;
+/// Most ticks simulated per rendered frame.
+const MAX_SIM_SPEED: u32 = 10;
+/// Chance per tick that each cell of a spawner laid with the spawner tool
+/// emits, a steady drizzle.
+const SPAWNER_RATE: f32 = 0.05;
+/// How many edits can be undone.
+const HISTORY_DEPTH: usize = 50;
+/// Placed in turn by the N key.
+const PATTERNS: [Pattern; 2] = [Pattern::Glider, Pattern::Blinker];
+const TARGET_FPS: u32 = 60;
+const SAVE_PATH: &str = "grid.sav";
 
-                let position = Point::new(x, y);
-                let new_position = material.update(self, position);
-                new_grid[new_position] = self[position].clone();
-            }
-        }
-        *self = new_grid;
-    }
+const BACKGROUND: Color = Color::RGB(0, 0, 0);
 
-    pub fn draw(&self, canvas: &mut Canvas<Window>) {
-        for (idx, cell) in self.data.iter().enumerate() {
-            if let Some(material) = cell {
-                let x = (idx % self.width) as i32;
-                let y = (idx / self.width) as i32;
-
-                canvas.set_draw_color(material.color());
-                let rect = Rect::from_center(
-                    Point::new(x, y).scale(PIXEL_SIZE as i32),
-                    PIXEL_SIZE as u32,
-                    PIXEL_SIZE as u32
-                );
-                canvas.fill_rect(rect);
+/// Cell rectangles grouped by color, so each color costs one `fill_rects`
+/// call instead of one call per cell.
+type Batches = HashMap<(u8, u8, u8), Vec<Rect>>;
+
+/// Submits `batches`, leaving them empty for reuse. Cells never overlap, so
+/// the order colors are drawn in doesn't affect the result. A batch that
+/// fails to draw doesn't stop the rest; every failure is reported together,
+/// and only a failure allocates.
+fn fill_batches(canvas: &mut Canvas<Window>, batches: &mut Batches) -> Result<(), String> {
+    let mut failed: Option<String> = None;
+    for (&(r, g, b), rects) in batches.iter_mut() {
+        if rects.is_empty() { continue; }
+
+        canvas.set_draw_color(Color::RGB(r, g, b));
+        let filled = canvas.fill_rects(rects);
+        rects.clear();
+        if let Err(error) = filled {
+            match &mut failed {
+                Some(errors) => {
+                    errors.push_str("; ");
+                    errors.push_str(&error);
+                },
+                None => failed = Some(error),
             }
         }
     }
-}
 
-impl Index<Point> for Grid {
-    type Output = Option<Box<Material>>;
+    failed.map_or(Ok(()), Err)
+}
 
-    fn index(&self, point: Point) -> &Self::Output {
-        let idx = point.y as usize * self.width + point.x as usize; 
+/// Draws onto an SDL canvas through a camera, batching cells by color.
+struct SdlRenderer<'a> {
+    canvas: &'a mut Canvas<Window>,
+    camera: &'a Camera,
+    batches: &'a mut Batches,
+}
 
-        &self.data[idx]
+impl SdlRenderer<'_> {
+    fn push(&mut self, point: Point, (r, g, b): (u8, u8, u8)) {
+        self.batches.entry((r, g, b))
+            .or_default()
+            .push(self.camera.cell_rect(point));
     }
 }
 
-impl IndexMut<Point> for Grid {
-    fn index_mut(&mut self, point: Point) -> &mut Self::Output {
-        let idx = point.y as usize * self.width + point.x as usize; 
+impl Renderer for SdlRenderer<'_> {
+    fn clear(&mut self) -> Result<(), String> {
+        self.canvas.set_draw_color(BACKGROUND);
+        self.canvas.clear();
+        Ok(())
+    }
 
-        &mut self.data[idx]
+    fn fill_cells(&mut self, cells: &[(Point, CellColor)]) -> Result<(), String> {
+        for &(point, color) in cells {
+            self.push(point, (color.r, color.g, color.b));
+        }
+        Ok(())
     }
-}
 
-#[derive(Clone, Copy)]
-pub struct Sand;
-
-impl Material for Sand {
-    fn update(&self, grid: &Grid, position: Point) -> Point {
-        let down = position.offset(0, 1);
-        if down.y >= grid.height as i32 { return position; }
-        let down_left = position.offset(-1, 1);
-        if down_left.x < 0 { return position; }
-        let down_right = position.offset(1, 1);
-        if down_right.x >= grid.width as i32 { return position; }
-        
-        if grid[down].is_none() {
-            down
-        } else if grid[down_left].is_none() {
-            down_left
-        } else if grid[down_right].is_none() {
-            down_right
-        } else {
-            position
+    fn clear_cells(&mut self, cells: &[Point]) -> Result<(), String> {
+        for &point in cells {
+            self.push(point, (BACKGROUND.r, BACKGROUND.g, BACKGROUND.b));
         }
+        Ok(())
+    }
+
+    /// Submits everything batched up since the last frame.
+    fn present(&mut self) -> Result<(), String> {
+        fill_batches(self.canvas, self.batches)
     }
-    
-    fn color(&self) -> Color {
-        Color::RGB(198, 178, 128)
+
+    fn is_visible(&self, point: Point) -> bool {
+        self.camera.is_visible(point)
     }
 }
 
-pub trait Material: MaterialClone {
-    fn update(&self, grid: &Grid, position: Point) -> Point;
+/// Writes the grid at one pixel per cell to a timestamped PNG in the working
+/// directory, returning its name.
+fn screenshot(grid: &Grid) -> Result<String, Box<dyn Error>> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+    let path = format!("screenshot-{}.png", timestamp);
+
+    image::save_buffer(
+        &path,
+        &grid.to_rgb_buffer(),
+        grid.width() as u32,
+        grid.height() as u32,
+        image::ColorType::Rgb8,
+    )?;
 
-    fn color(&self) -> Color;
+    Ok(path)
 }
 
-pub trait MaterialClone {
-    fn clone_box(&self) -> Box<Material>;
+/// Writes out a finished recording into a directory of its own, named for
+/// when it was saved, returning the directory and the number of frames.
+fn save_recording(recording: Recording) -> Result<(String, usize), Box<dyn Error>> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+    let dir = format!("recording-{}", timestamp);
+    let frames = recording.save(Path::new(&dir))?;
+
+    Ok((dir, frames))
 }
 
-impl<T: 'static + Material + Clone> MaterialClone for T {
-    fn clone_box(&self) -> Box<Material> {
-        Box::new(self.clone())
+/// Saves a recording that has just been stopped, saying where it went.
+fn report_recording(recording: Recording, hud: &mut Hud) {
+    if recording.is_empty() { return; }
+
+    match save_recording(recording) {
+        Ok((dir, frames)) => {
+            println!("saved {} frames to {}", frames, dir);
+            hud.flash(format!("saved {} frames", frames));
+        },
+        Err(error) => eprintln!("failed to save recording: {}", error),
     }
 }
 
-impl Clone for Box<Material> {
-    fn clone(&self) -> Self {
-        self.clone_box()
-    }
+/// The material each key selects for painting, by registry name.
+const MATERIAL_KEYS: &[(Keycode, &str)] = &[
+    (Keycode::Num1, "sand"),
+    (Keycode::Num2, "water"),
+    (Keycode::Num3, "stone"),
+    (Keycode::Num4, "lava"),
+    (Keycode::Num5, "steam"),
+    (Keycode::Num6, "fire"),
+    (Keycode::Num7, "wood"),
+    (Keycode::Num8, "smoke"),
+    (Keycode::Num9, "ice"),
+    (Keycode::Num0, "acid"),
+    (Keycode::O, "oil"),
+    (Keycode::I, "metal"),
+    (Keycode::U, "torch"),
+    (Keycode::J, "drain"),
+];
+
+/// Materials an initial image's pixels are matched against, by their own
+/// colors.
+fn palette(registry: &MaterialRegistry) -> Vec<(CellColor, Particle)> {
+    registry.names()
+        .filter_map(|name| registry.create(name))
+        .map(|material| (material.color(), material))
+        .collect()
 }
 
-const WIDTH: usize = 1920;
-const HEIGHT: usize = 1024;
-const PIXEL_SIZE: u8 = 5;
+/// What a left-button drag does when a modifier is held as it starts.
+#[derive(Clone, Copy)]
+enum Gesture {
+    /// Shift: fills the rectangle dragged out with the brush material.
+    Fill,
+    /// Ctrl: copies the rectangle dragged out.
+    Copy,
+    /// Alt: stamps the last copy where the button is released.
+    Stamp,
+    /// With the spawner tool on: lays a spawner of the brush material over
+    /// the rectangle dragged out.
+    Spawn,
+}
 
-pub fn main() {
-    let sdl_context = sdl2::init().unwrap();
-    let video_subsystem = sdl_context.video().unwrap();
- 
-    let mut grid = Grid::new(WIDTH / PIXEL_SIZE as usize, HEIGHT / PIXEL_SIZE as usize);
+fn material_for_key(registry: &MaterialRegistry, keycode: Keycode) -> Option<Particle> {
+    MATERIAL_KEYS.iter()
+        .find(|&&(key, _)| key == keycode)
+        .and_then(|&(_, name)| registry.create(name))
+}
+
+pub fn main() -> Result<(), Box<dyn Error>> {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) if options.help => {
+            println!("{}", options::USAGE);
+            return Ok(());
+        },
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("error: {}\n\n{}", error, options::USAGE);
+            process::exit(2);
+        },
+    };
+    let registry = MaterialRegistry::builtin();
+
+    let (grid_width, grid_height) = options.grid_size();
+    let mut grid = match &options.image {
+        Some(path) => Grid::from_image(Path::new(path), grid_width, grid_height, &palette(&registry))
+            .map_err(|error| format!("failed to load {}: {}", path, error))?,
+        None => Grid::try_new(grid_width, grid_height)?,
+    };
+    if let Some(path) = &options.palette {
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("failed to read {}: {}", path, error))?;
+        let palette = Palette::from_ron_with(&text, &registry)
+            .map_err(|error| format!("failed to load {}: {}", path, error))?;
+        grid.set_palette(palette);
+    }
+    let seed = options.seed.unwrap_or_else(rand::random);
+    println!("seed {}, rerun with --seed {} to replay", seed, seed);
+    grid.reseed(seed);
 
-    let window = video_subsystem.window("rust-sdl2 demo", WIDTH as u32, HEIGHT as u32)
+    let sdl_context = sdl2::init()
+        .map_err(|error| format!("failed to initialize SDL: {}", error))?;
+    let video_subsystem = sdl_context.video()
+        .map_err(|error| format!("failed to initialize video, is a display available? {}", error))?;
+
+    // The picker sits to the right of the playfield.
+    let window = video_subsystem.window("rust-sdl2 demo", options.width + picker::WIDTH, options.height)
         .position_centered()
         .build()
-        .unwrap();
+        .map_err(|error| format!("failed to create window: {}", error))?;
 
-    let mut canvas = window.into_canvas().build().unwrap();
+    let mut builder = window.into_canvas();
+    if options.present == Present::Vsync {
+        builder = builder.present_vsync();
+    }
+    let mut canvas = builder.build()
+        .map_err(|error| format!("failed to create renderer: {}", error))?;
+    println!("presenting frames {}", options.present);
  
-    canvas.set_draw_color(Color::RGB(0, 0, 0));
+    canvas.set_draw_color(BACKGROUND);
     canvas.clear();
     canvas.present();
 
-    let mut event_pump = sdl_context.event_pump().unwrap();
+    // The window's backbuffer is undefined after each present, so frames are
+    // accumulated on a texture that keeps its contents between ticks.
+    let texture_creator = canvas.texture_creator();
+    let mut frame = texture_creator
+        .create_texture_target(None, options.width, options.height)?;
+    let mut full_redraw = true;
+    let viewport = (options.width, options.height);
+    let mut camera = Camera::new(options.pixel_size, viewport, (grid.width(), grid.height()));
+    let picker = Picker::new(&registry, grid.palette(), options.width as i32, options.height);
+    // Set while a left press that started on the picker is held, so it
+    // doesn't paint.
+    let mut picking = false;
+    // The window position and view offset a middle-button drag started
+    // from.
+    let mut drag: Option<((i32, i32), Point)> = None;
+    let mut mouse_position = (0, 0);
+    // The modified left-button drag in progress, and the cell it started on.
+    let mut gesture_start: Option<(Gesture, Point)> = None;
+    // Cells last copied with a ctrl-drag, and the size of the region.
+    let mut clipboard: Option<(Vec<Cell>, u32, u32)> = None;
+    // Whether left drags lay spawners rather than painting.
+    let mut spawner_tool = false;
+    // Where the brush was last painted in the current stroke.
+    let mut stroke_end: Option<Point> = None;
+    // Kept across frames so the per-color buffers are only allocated once.
+    let mut batches = Batches::new();
+
+    let ttf_context = sdl2::ttf::init()?;
+    let mut hud = Hud::new(&ttf_context);
+
+    let mut history = History::new(HISTORY_DEPTH);
+    // Taken and rolled back to by key, for trying things out.
+    let mut checkpoint = None;
+    // Frames kept since recording was started with shift+R.
+    let mut recording: Option<Recording> = None;
+    let mut brush_radius = BRUSH_RADIUS;
+    let mut brush_shape = BrushShape::Circle;
+    let mut current_material: Particle = Particle::Sand(Sand);
+    let mut paused = false;
+    // Ticks simulated per rendered frame.
+    let mut sim_speed = 1;
+    // Which of `PATTERNS` the pattern key places next.
+    let mut next_pattern = 0;
+    // Set by the step key to run a single tick while paused.
+    let mut step = false;
+
+    let frame_time = Duration::from_secs(1) / TARGET_FPS;
+
+    let mut event_pump = sdl_context.event_pump()?;
+    let mut input = Input::new();
     'running: loop {
+        let frame_start = Instant::now();
+
         for event in event_pump.poll_iter() {
+            input.handle(&event);
             match event {
-                Event::Quit {..} |
-                Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
-                    break 'running
+                Event::Quit {..} => break 'running,
+                Event::KeyDown { keycode: Some(Keycode::LeftBracket), .. } => {
+                    brush_radius = (brush_radius - 1).max(0);
+                },
+                Event::KeyDown { keycode: Some(Keycode::RightBracket), .. } => {
+                    brush_radius = (brush_radius + 1).min(MAX_BRUSH_RADIUS);
+                },
+                Event::KeyDown { keycode: Some(Keycode::S), .. } => {
+                    if let Err(error) = grid.save(Path::new(SAVE_PATH)) {
+                        eprintln!("failed to save {}: {}", SAVE_PATH, error);
+                    }
+                },
+                Event::KeyDown { keycode: Some(Keycode::L), .. } => {
+                    match Grid::load(Path::new(SAVE_PATH)) {
+                        Ok(mut loaded) => {
+                            loaded.set_palette(grid.palette().clone());
+                            loaded.reseed(seed);
+                            grid = loaded;
+                            history = History::new(HISTORY_DEPTH);
+                            camera = Camera::new(
+                                options.pixel_size, viewport, (grid.width(), grid.height()),
+                            );
+                            full_redraw = true;
+                        },
+                        Err(error) => eprintln!("failed to load {}: {}", SAVE_PATH, error),
+                    }
+                },
+                Event::MouseWheel { y, .. } => {
+                    full_redraw |= camera.zoom_by(y, mouse_position);
+                },
+                Event::MouseButtonDown { mouse_btn: MouseButton::Middle, x, y, .. } => {
+                    drag = Some(((x, y), camera.offset()));
+                },
+                Event::MouseButtonUp { mouse_btn: MouseButton::Middle, .. } => {
+                    drag = None;
+                },
+                Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. } if picker.contains(x, y) => {
+                    picking = true;
+                    if let Some(material) = picker.material_at(x, y).and_then(|name| registry.create(name)) {
+                        hud.flash(format!("{}: {}", material.name(), material.description()));
+                        current_material = material;
+                    }
+                },
+                Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. } => {
+                    let gesture = if input.shift() {
+                        Some(Gesture::Fill)
+                    } else if input.ctrl() {
+                        Some(Gesture::Copy)
+                    } else if input.alt() {
+                        Some(Gesture::Stamp)
+                    } else if spawner_tool {
+                        Some(Gesture::Spawn)
+                    } else {
+                        None
+                    };
+                    gesture_start = gesture.map(|gesture| (gesture, camera.cell_at(x, y)));
+                },
+                Event::MouseButtonUp { mouse_btn: MouseButton::Left, .. } if picking => {
+                    picking = false;
+                },
+                Event::MouseButtonUp { mouse_btn: MouseButton::Left, x, y, .. } => {
+                    let end = camera.cell_at(x, y);
+                    match gesture_start.take() {
+                        Some((Gesture::Fill, start)) => {
+                            let rect = CellRect::from_corners(start, end);
+                            history.fill_rect(&mut grid, rect, &*current_material);
+                            history.finish();
+                        },
+                        Some((Gesture::Copy, start)) => {
+                            let rect = CellRect::from_corners(start, end);
+                            clipboard = Some((grid.copy_region(rect), rect.width, rect.height));
+                            hud.flash(format!("copied {}x{}", rect.width, rect.height));
+                        },
+                        Some((Gesture::Stamp, _)) => {
+                            if let Some((buffer, width, height)) = &clipboard {
+                                history.paste_region(&mut grid, end, buffer, *width, *height);
+                                history.finish();
+                            }
+                        },
+                        Some((Gesture::Spawn, start)) => {
+                            let rect = CellRect::from_corners(start, end);
+                            grid.add_spawner(Spawner::new(rect, &*current_material, SPAWNER_RATE));
+                        },
+                        None => {},
+                    }
+                },
+                Event::MouseMotion { x, y, .. } => {
+                    mouse_position = (x, y);
+                    if let Some(((start_x, start_y), offset)) = drag {
+                        let size = camera.cell_size() as i32;
+                        camera.pan_to(offset.offset((start_x - x) / size, (start_y - y) / size));
+                        full_redraw = true;
+                    }
+                },
+                Event::KeyDown { keycode: Some(Keycode::Space), .. } => {
+                    paused = !paused;
+                },
+                Event::KeyDown { keycode: Some(Keycode::Equals | Keycode::KpPlus), .. } => {
+                    sim_speed = (sim_speed + 1).min(MAX_SIM_SPEED);
+                },
+                Event::KeyDown { keycode: Some(Keycode::Minus | Keycode::KpMinus), .. } => {
+                    sim_speed = (sim_speed - 1).max(1);
+                },
+                Event::KeyDown { keycode: Some(Keycode::Period), .. } => {
+                    step = true;
+                },
+                Event::KeyDown { keycode: Some(Keycode::K), .. } => {
+                    checkpoint = Some(grid.snapshot());
+                },
+                Event::KeyDown { keycode: Some(Keycode::R), .. } if input.shift() => {
+                    match recording.take() {
+                        Some(finished) => report_recording(finished, &mut hud),
+                        None => {
+                            recording = Some(Recording::new(options.record_every));
+                            hud.flash("recording".into());
+                        },
+                    }
+                },
+                Event::KeyDown { keycode: Some(Keycode::R), .. } => {
+                    if let Some(checkpoint) = &checkpoint {
+                        grid.restore(checkpoint);
+                        history = History::new(HISTORY_DEPTH);
+                        full_redraw = true;
+                    }
+                },
+                Event::KeyDown { keycode: Some(Keycode::C), .. } => {
+                    history.clear(&mut grid);
+                    full_redraw = true;
+                },
+                Event::KeyDown { keycode: Some(Keycode::Z), .. } if input.ctrl() => {
+                    full_redraw |= history.undo(&mut grid);
+                },
+                Event::KeyDown { keycode: Some(Keycode::Y), .. } if input.ctrl() => {
+                    full_redraw |= history.redo(&mut grid);
+                },
+                Event::KeyDown { keycode: Some(Keycode::Tab), .. } => {
+                    let steps = if input.shift() { -1 } else { 1 };
+                    if let Some(material) = registry.cycle(&*current_material, steps) {
+                        hud.flash(format!("{}: {}", material.name(), material.description()));
+                        current_material = material;
+                    }
+                },
+                Event::KeyDown { keycode: Some(Keycode::E), .. } => {
+                    grid.electrify(camera.cell_at(mouse_position.0, mouse_position.1));
+                },
+                Event::KeyDown { keycode: Some(Keycode::H), .. } => {
+                    grid.flip_horizontal();
+                },
+                Event::KeyDown { keycode: Some(Keycode::V), .. } => {
+                    grid.flip_vertical();
+                },
+                Event::KeyDown { keycode: Some(Keycode::G), .. } => {
+                    // A quarter turn clockwise on screen.
+                    let (gx, gy) = grid.gravity();
+                    grid.set_gravity((-gy, gx));
+                },
+                Event::KeyDown { keycode: Some(Keycode::W), .. } => {
+                    // Still, then blowing right, then left.
+                    let wind = if grid.wind() == 0.0 {
+                        WIND
+                    } else if grid.wind() > 0.0 {
+                        -WIND
+                    } else {
+                        0.0
+                    };
+                    grid.set_wind(wind);
+                },
+                Event::KeyDown { keycode: Some(Keycode::M), .. } => {
+                    let mode = match grid.mode() {
+                        Mode::Physics => Mode::Life,
+                        Mode::Life => Mode::Physics,
+                    };
+                    grid.set_mode(mode);
+                    if mode == Mode::Life {
+                        current_material = Particle::Life(Life);
+                    }
+                },
+                Event::KeyDown { keycode: Some(Keycode::N), .. } => {
+                    let cursor = camera.cell_at(mouse_position.0, mouse_position.1);
+                    grid.paint_pattern(PATTERNS[next_pattern], cursor);
+                    next_pattern = (next_pattern + 1) % PATTERNS.len();
+                },
+                Event::KeyDown { keycode: Some(Keycode::T), .. } => {
+                    let view = match grid.view() {
+                        View::Materials => View::Temperature,
+                        View::Temperature => View::Materials,
+                    };
+                    grid.set_view(view);
+                    full_redraw = true;
+                },
+                Event::KeyDown { keycode: Some(Keycode::B), .. } => {
+                    let (boundary, message) = match grid.boundary() {
+                        BoundaryMode::Solid => (BoundaryMode::Wrap, "edges wrap around"),
+                        BoundaryMode::Wrap => (BoundaryMode::Void, "edges are open"),
+                        BoundaryMode::Void => (BoundaryMode::Solid, "edges are solid"),
+                    };
+                    grid.set_boundary(boundary);
+                    hud.flash(message.to_string());
+                },
+                Event::KeyDown { keycode: Some(Keycode::Q), .. } => {
+                    let (order, message) = match grid.update_order() {
+                        UpdateOrder::Simultaneous => (UpdateOrder::BottomUp, "moving bottom up"),
+                        UpdateOrder::BottomUp => (UpdateOrder::Simultaneous, "moving simultaneously"),
+                    };
+                    grid.set_update_order(order);
+                    hud.flash(message.to_string());
+                },
+                Event::KeyDown { keycode: Some(Keycode::X), .. } => {
+                    spawner_tool = !spawner_tool;
+                    let state = if spawner_tool { "on" } else { "off" };
+                    hud.flash(format!("spawner tool {}", state));
+                },
+                Event::KeyDown { keycode: Some(Keycode::D), .. } => {
+                    brush_shape = brush_shape.next();
+                    hud.flash(format!("{} brush", brush_shape.name()));
+                },
+                Event::KeyDown { keycode: Some(Keycode::A), .. } => {
+                    grid.set_show_awake(!grid.shows_awake());
+                    full_redraw = true;
+                },
+                Event::KeyDown { keycode: Some(Keycode::F), .. } => {
+                    hud.visible = !hud.visible;
+                },
+                Event::KeyDown { keycode: Some(Keycode::P), .. } => {
+                    match screenshot(&grid) {
+                        Ok(path) => println!("saved {}", path),
+                        Err(error) => eprintln!("failed to save screenshot: {}", error),
+                    }
+                },
+                Event::KeyDown { keycode: Some(keycode), .. } => {
+                    if let Some(material) = material_for_key(&registry, keycode) {
+                        current_material = material;
+                    }
                 },
                 _ => {}
             }
         }
 
+        if input.is_held(Keycode::Escape) {
+            break 'running;
+        }
+
+        // Held arrow keys scroll steadily, rather than at the key repeat
+        // rate after the repeat delay.
+        for &(keycode, dx, dy) in PAN_KEYS.iter() {
+            if input.is_held(keycode) {
+                camera.pan(dx * PAN_STEP, dy * PAN_STEP);
+                full_redraw = true;
+            }
+        }
+        input.end_frame();
+
         let mouse = event_pump.mouse_state();
-        if(mouse.left()) {
-            let cursor = Point::new(
-                mouse.x() / PIXEL_SIZE as i32, 
-                mouse.y() / PIXEL_SIZE as i32);
-            grid[cursor] = Some(Box::new(Sand));
+        let cursor = camera.cell_at(mouse.x(), mouse.y());
+        // Strokes join up with where the cursor was last frame, so quick
+        // movements don't leave gaps.
+        let from = stroke_end.unwrap_or(cursor);
+        // Gestures only act once the button is released.
+        if mouse.left() && gesture_start.is_none() && !picking {
+            history.paint_line(&mut grid, from, cursor, brush_radius, brush_shape, &*current_material);
+            stroke_end = Some(cursor);
+        } else if mouse.right() {
+            history.erase_line(&mut grid, from, cursor, brush_radius);
+            grid.remove_spawners(cursor, brush_radius);
+            stroke_end = Some(cursor);
+        } else {
+            // Each press of a button is one edit, however long it's held.
+            history.finish();
+            stroke_end = None;
         }
         // The rest of the game loop goes here...
 
-        grid.update();
+        // The brush is applied once a frame above, so it keeps up however
+        // many ticks run in between.
+        if step {
+            grid.update_parallel();
+            step = false;
+        } else if !paused {
+            for _ in 0 .. sim_speed {
+                grid.update_parallel();
+            }
+        }
+
+        // A frame that fails to draw is reported and skipped rather than
+        // ending the session.
+        let mut drawn = Ok(());
+        canvas.with_texture_canvas(&mut frame, |target| {
+            let mut renderer = SdlRenderer { canvas: target, camera: &camera, batches: &mut batches };
+            // Temperatures change without anything moving, and cells fall
+            // asleep without changing, so the heat view and the awake
+            // overlay are redrawn in full every frame.
+            drawn = if full_redraw || grid.view() == View::Temperature || grid.shows_awake() {
+                grid.draw(&mut renderer)
+            } else {
+                grid.draw_dirty(&mut renderer)
+            };
+        })?;
+        // Whatever a failed frame missed is only put right by drawing the
+        // next one in full, since the dirty cells are about to be forgotten.
+        full_redraw = drawn.is_err();
+        if let Err(error) = drawn {
+            eprintln!("failed to draw grid: {}", error);
+        }
+        grid.clear_dirty();
+        if let Some(recording) = &mut recording {
+            recording.capture(&grid);
+        }
 
-        canvas.set_draw_color(Color::RGB(0, 0, 0));
-        canvas.clear();
-        grid.draw(&mut canvas);
+        if let Err(error) = canvas.copy(&frame, None, Rect::new(0, 0, options.width, options.height)) {
+            eprintln!("failed to copy frame: {}", error);
+        }
+        if let Err(error) = picker.draw(&mut canvas, &*current_material) {
+            eprintln!("failed to draw picker: {}", error);
+        }
+        hud.frame(&mut canvas, &texture_creator, &grid, sim_speed, &*current_material, brush_shape);
+        if let Err(error) = hud.draw(&mut canvas) {
+            eprintln!("failed to draw HUD: {}", error);
+        }
         canvas.present();
+
+        // A slow frame simply doesn't sleep rather than trying to catch up.
+        let remaining = frame_time.checked_sub(frame_start.elapsed());
+        if let Some(remaining) = remaining.filter(|_| options.present != Present::Uncapped) {
+            thread::sleep(remaining);
+        }
     }
+
+    // Closing the window stops a recording in progress.
+    if let Some(finished) = recording {
+        report_recording(finished, &mut hud);
+    }
+
+    Ok(())
 }
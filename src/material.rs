@@ -0,0 +1,410 @@
+use rand::{
+    Rng,
+    RngCore,
+};
+
+use crate::{
+    grid::NEIGHBOURS,
+    Color,
+    Grid,
+    Point,
+};
+
+mod acid;
+mod crystal;
+mod drain;
+mod fire;
+mod glass;
+mod gunpowder;
+mod ice;
+mod lava;
+mod life;
+mod metal;
+mod mud;
+mod oil;
+mod particle;
+mod plant;
+mod sand;
+mod smoke;
+mod steam;
+mod stone;
+mod torch;
+mod water;
+mod wood;
+
+pub use acid::Acid;
+pub use crystal::Crystal;
+pub use drain::Drain;
+pub use fire::Fire;
+pub use glass::Glass;
+pub use gunpowder::Gunpowder;
+pub use ice::Ice;
+pub use lava::Lava;
+pub use life::Life;
+pub use metal::Metal;
+pub use mud::Mud;
+pub use oil::Oil;
+pub use particle::Particle;
+pub use plant::Plant;
+pub use sand::Sand;
+pub use smoke::Smoke;
+pub use steam::Steam;
+pub use stone::Stone;
+pub use torch::Torch;
+pub use water::Water;
+pub use wood::Wood;
+
+/// How far to either side liquids look for somewhere lower to flow to.
+const LEVEL_DISTANCE: i32 = 16;
+
+/// The contents of one grid cell: a particle, or nothing.
+///
+/// Particles are held by value as a [`Particle`], so a cell takes a few
+/// bytes and making, copying or moving a particle never touches the heap.
+/// The set of materials is closed: adding one means implementing
+/// [`Material`] and giving it a variant of [`Particle`]. The `allocations`
+/// benchmark counts how many allocations an update makes.
+pub type Cell = Option<Particle>;
+
+/// Materials must be `Send + Sync` so that [`Grid::update_parallel`] can
+/// share the grid between threads.
+pub trait Material: Send + Sync {
+    /// Chooses where the material at `position` moves this tick. `rng` is
+    /// owned by the grid, so seeded grids replay identically.
+    fn update(&self, grid: &Grid, position: Point, rng: &mut dyn RngCore) -> Point;
+
+    fn color(&self) -> Color;
+
+    /// The color drawn for this material at the given cell temperature, for
+    /// materials that glow or change shade as they heat up.
+    fn heated_color(&self, _temperature: f32) -> Color {
+        self.color()
+    }
+
+    /// A copy of this material as a particle, for putting it in a cell.
+    fn particle(&self) -> Particle;
+
+    /// Stable identifier written to save files. Must be unique and never
+    /// reused, with `0` reserved for empty cells.
+    fn id(&self) -> u16;
+
+    /// Short lowercase name shown to players and used in level and palette
+    /// files, such as `"sand"`.
+    fn name(&self) -> &'static str;
+
+    /// One sentence on how the material behaves, for pickers and tooltips.
+    fn description(&self) -> &'static str;
+
+    /// How far, per channel, individual particles' colors may stray from
+    /// [`Material::color`] to give a natural texture.
+    fn shade_variation(&self) -> u8 {
+        0
+    }
+
+    /// Relative weight used to decide whether one material sinks through
+    /// another.
+    fn density(&self) -> f32 {
+        1.0
+    }
+
+    /// How thick a liquid is, from `0` for running freely to `255` for
+    /// barely spreading at all: the chance out of 256 that it holds still
+    /// each tick rather than moving sideways. Only liquids consult it.
+    fn viscosity(&self) -> u8 {
+        0
+    }
+
+    /// How steeply a powder piles, from `0` for sliding down any slope to
+    /// `255` for barely sliding at all: the chance out of 256 that a grain
+    /// resting on its own kind with only diagonals open holds still rather
+    /// than sliding. Coarse grains lock together and stand steeper than
+    /// fine ones. Only powders consult it.
+    fn repose(&self) -> u8 {
+        0
+    }
+
+    /// How strongly wind pushes this material around, from `0.0` for not at
+    /// all to `1.0` for being carried along every tick at full strength.
+    fn wind_response(&self) -> f32 {
+        0.0
+    }
+
+    /// How readily heat flows through this material, from `0.0` for a
+    /// perfect insulator to `1.0` for a perfect conductor.
+    fn heat_transfer(&self) -> f32 {
+        0.5
+    }
+
+    /// Degrees added to this material's own cell every tick it is awake,
+    /// which then diffuse outwards. Heat sources should also be restless so
+    /// they keep burning once their surroundings settle.
+    fn heat_output(&self) -> f32 {
+        0.0
+    }
+
+    /// Share of the cells under the brush that painting fills, at random,
+    /// from `0.0` for none to `1.0` for all of them. Below one, liquids and
+    /// gases drip and puff out rather than landing in a solid block.
+    fn spawn_density(&self) -> f32 {
+        1.0
+    }
+
+    /// Temperature a newly painted particle or reaction product starts at,
+    /// instead of taking on its cell's.
+    fn spawn_temperature(&self) -> Option<f32> {
+        None
+    }
+
+    /// Returning a material turns this one into it at the given temperature,
+    /// such as ice melting. Checked for awake cells and for cells away from
+    /// ambient temperature.
+    fn phase_change(&self, _temperature: f32) -> Option<Particle> {
+        None
+    }
+
+    /// Whether a connected body of this material finds its level under its
+    /// own pressure, rising up one side of a U bend as far as the other side
+    /// stands. See `Grid::equalize`.
+    fn finds_level(&self) -> bool {
+        false
+    }
+
+    /// Whether electric charge travels through this material, see
+    /// [`Grid::electrify`].
+    fn conducts(&self) -> bool {
+        false
+    }
+
+    /// Whether acid leaves this material alone, so it can hold acid.
+    fn acid_resistant(&self) -> bool {
+        false
+    }
+
+    /// Static materials never move, so `Grid::update` skips calling
+    /// [`Material::update`] for them.
+    fn is_static(&self) -> bool {
+        false
+    }
+
+    /// Whether fire may spread into this material. Fire only consults this
+    /// flag, so new materials become burnable just by opting in.
+    fn flammable(&self) -> bool {
+        false
+    }
+
+    /// How far explosive materials blast when they catch fire, see
+    /// [`Grid::explode`].
+    fn blast_radius(&self) -> Option<i32> {
+        None
+    }
+
+    /// Restless materials change every tick even when they don't move, such
+    /// as flickering fire, so they never fall asleep.
+    fn is_restless(&self) -> bool {
+        false
+    }
+
+    /// Whether the particle, come to rest at `position`, may yet change
+    /// where it lies, such as sand beside water that may soak into mud.
+    /// Unsettled cells go on ticking and reacting while asleep, without
+    /// waking anything around them the way restless ones do, and the grid
+    /// isn't [settled](Grid::is_settled) until none are left.
+    fn is_unsettled(&self, _grid: &Grid, _position: Point) -> bool {
+        false
+    }
+
+    /// Advances any per-cell state, such as a flicker, once per tick while
+    /// the cell is awake. Returning `Some` replaces the cell's contents,
+    /// with `Some(None)` emptying it.
+    fn tick(&mut self, _rng: &mut dyn RngCore) -> Option<Cell> {
+        None
+    }
+
+    /// Ticks left before a short-lived material expires. Materials opt in by
+    /// returning their counter, which the grid counts down once per tick
+    /// while the cell is awake.
+    fn life(&mut self) -> Option<&mut u16> {
+        None
+    }
+
+    /// What a material becomes once its life runs out, with `None` for
+    /// vanishing.
+    fn expire(&self) -> Cell {
+        None
+    }
+
+    /// Counts down the material's life, returning its replacement once it
+    /// has expired. Materials without a life never expire.
+    fn tick_life(&mut self) -> Option<Cell> {
+        let expired = match self.life() {
+            Some(0) => true,
+            Some(life) => {
+                *life -= 1;
+                false
+            },
+            None => false,
+        };
+
+        if expired { Some(self.expire()) } else { None }
+    }
+
+    /// An empty cell this material grows a copy of itself into this tick,
+    /// if any, such as a plant creeping along. Called for every awake cell,
+    /// static or not.
+    fn spread(&self, _grid: &Grid, _position: Point, _rng: &mut dyn RngCore) -> Option<Point> {
+        None
+    }
+
+    /// Whether [`Material::spread`] may grow over a cell holding `occupant`,
+    /// replacing it, as well as into empty cells, such as a crystal
+    /// freezing the water around it.
+    fn spreads_over(&self, _occupant: &dyn Material) -> bool {
+        false
+    }
+
+    /// Called by `Grid::update` for each of the four neighbours of every
+    /// awake cell. Returning replacements for this cell and the neighbour,
+    /// in that order, turns both into something new, with `None` emptying
+    /// a cell.
+    fn react(
+        &self,
+        _neighbor: &dyn Material,
+        _rng: &mut dyn RngCore,
+    ) -> Option<(Cell, Cell)> {
+        None
+    }
+}
+
+/// Maps an offset relative to the grid's gravity onto the grid, with `dy`
+/// pointing down and `dx` to the right when facing down. Materials describe
+/// their moves this way so they follow gravity wherever it points.
+pub(crate) fn relative(grid: &Grid, position: Point, dx: i32, dy: i32) -> Point {
+    let (gx, gy) = grid.gravity();
+    position.offset(dx * gy + dy * gx, dy * gy - dx * gx)
+}
+
+/// Where a material of the given density moves to, heading for the first of
+/// `candidates` it can, as [`Grid::destination`] decides.
+pub(crate) fn first_free(grid: &Grid, density: f32, candidates: &[Point]) -> Option<Point> {
+    candidates.iter().find_map(|&p| grid.destination(p, density))
+}
+
+/// Whether any of the four cells beside `position` holds the material with
+/// `id`.
+pub(crate) fn touching(grid: &Grid, position: Point, id: u16) -> bool {
+    NEIGHBOURS.iter().any(|&(dx, dy)| {
+        grid.material_at(position.offset(dx, dy)).is_some_and(|material| material.id() == id)
+    })
+}
+
+/// Powder movement: fall straight down, or slide off to either diagonal so
+/// that grains heap up into piles. The higher the powder's repose, the more
+/// often a grain resting on grains of its own kind holds still instead of
+/// sliding, and the steeper its piles. Anything else gives no grip, so
+/// grains roll all the way down a bare slope, a diagonal at a time, until
+/// they come to rest against something. They only ever move down, so they
+/// can't rock back and forth between two spots.
+pub(crate) fn pile(grid: &Grid, position: Point, powder: &dyn Material, rng: &mut dyn RngCore) -> Point {
+    let density = powder.density();
+    let down = relative(grid, position, 0, 1);
+    let (left, right) = (relative(grid, position, -1, 1), relative(grid, position, 1, 1));
+
+    if let Some(fall) = first_free(grid, density, &[down]) {
+        return fall;
+    }
+    let on_own_kind = grid.get(down)
+        .and_then(|cell| cell.as_ref())
+        .is_some_and(|below| below.id() == powder.id());
+    let repose = powder.repose();
+    if on_own_kind && repose > 0 && rng.gen::<u8>() < repose {
+        return position;
+    }
+
+    // Pick which diagonal to try first at random so piles don't lean.
+    let (first, second) = if rng.gen() { (left, right) } else { (right, left) };
+    first_free(grid, density, &[first, second]).unwrap_or(position)
+}
+
+/// Liquid movement: fall straight down or diagonally, and otherwise head
+/// for the nearest drop along the surface, so that pools level out instead
+/// of slowly diffusing. With no drop in reach, spread sideways. The more
+/// viscous the liquid, the more often it stays put instead of moving
+/// sideways at all.
+pub(crate) fn flow(grid: &Grid, position: Point, liquid: &dyn Material, rng: &mut dyn RngCore) -> Point {
+    let density = liquid.density();
+    let falls = [
+        relative(grid, position, 0, 1),
+        relative(grid, position, -1, 1),
+        relative(grid, position, 1, 1),
+    ];
+    let sideways = [relative(grid, position, -1, 0), relative(grid, position, 1, 0)];
+
+    if let Some(fall) = first_free(grid, density, &falls) {
+        return fall;
+    }
+    let viscosity = liquid.viscosity();
+    if viscosity > 0 && rng.gen::<u8>() < viscosity {
+        return position;
+    }
+
+    nearest_drop(grid, position, density)
+        .or_else(|| first_free(grid, density, &sideways))
+        .unwrap_or(position)
+}
+
+/// The nearest cell level with `position` with room below it, reached along
+/// a run of empty cells no more than `LEVEL_DISTANCE` long. Ties go left.
+fn nearest_drop(grid: &Grid, position: Point, density: f32) -> Option<Point> {
+    let mut open = [true, true];
+
+    for distance in 1 ..= LEVEL_DISTANCE {
+        for (side, dx) in [(0, -1), (1, 1)] {
+            if !open[side] { continue; }
+
+            let cell = relative(grid, position, dx * distance, 0);
+            if !matches!(grid.get(cell), Some(None)) {
+                open[side] = false;
+                continue;
+            }
+
+            let below = relative(grid, position, dx * distance, 1);
+            if grid.in_bounds(below) && grid.is_passable(below, density) {
+                return Some(cell);
+            }
+        }
+
+        if open == [false, false] { break; }
+    }
+
+    None
+}
+
+/// Rebuilds a material from the [`Material::id`] it was saved with.
+pub(crate) fn from_id(id: u16) -> Option<Particle> {
+    let particle = match id {
+        1 => Particle::Sand(Sand),
+        2 => Particle::Water(Water),
+        3 => Particle::Stone(Stone),
+        4 => Particle::Lava(Lava),
+        5 => Particle::Steam(Steam),
+        6 => Particle::Fire(Fire::new()),
+        7 => Particle::Wood(Wood),
+        8 => Particle::Smoke(Smoke::new()),
+        9 => Particle::Ice(Ice),
+        10 => Particle::Acid(Acid),
+        11 => Particle::Oil(Oil),
+        12 => Particle::Life(Life),
+        13 => Particle::Glass(Glass),
+        14 => Particle::Plant(Plant),
+        15 => Particle::Gunpowder(Gunpowder),
+        16 => Particle::Metal(Metal),
+        17 => Particle::Torch(Torch),
+        18 => Particle::Drain(Drain),
+        19 => Particle::Crystal(Crystal),
+        20 => Particle::Mud(Mud::new()),
+        _ => return None,
+    };
+    debug_assert_eq!(particle.id(), id);
+
+    Some(particle)
+}
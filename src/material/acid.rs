@@ -0,0 +1,107 @@
+use rand::{
+    Rng,
+    RngCore,
+};
+
+use crate::{
+    material,
+    Cell,
+    Color,
+    Grid,
+    Material,
+    Particle,
+    Point,
+};
+
+/// Chance per tick that acid eats into a neighbour, using itself up.
+const DISSOLVE_CHANCE: f64 = 0.05;
+
+/// A corrosive liquid that dissolves whatever it touches, unless it is
+/// [`Material::acid_resistant`].
+#[derive(Clone, Copy)]
+pub struct Acid;
+
+impl Material for Acid {
+    fn update(&self, grid: &Grid, position: Point, rng: &mut dyn RngCore) -> Point {
+        material::flow(grid, position, self, rng)
+    }
+
+    fn color(&self) -> Color {
+        Color::rgb(140, 210, 40)
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Acid(*self)
+    }
+
+    fn id(&self) -> u16 {
+        10
+    }
+
+    fn name(&self) -> &'static str {
+        "acid"
+    }
+
+    fn description(&self) -> &'static str {
+        "Corrosive liquid that eats through most materials."
+    }
+
+    fn density(&self) -> f32 {
+        1.1
+    }
+
+    fn acid_resistant(&self) -> bool {
+        true
+    }
+
+    fn react(
+        &self,
+        neighbor: &dyn Material,
+        rng: &mut dyn RngCore,
+    ) -> Option<(Cell, Cell)> {
+        if !neighbor.acid_resistant() && rng.gen_bool(DISSOLVE_CHANCE) {
+            Some((None, None))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Sand,
+        Stone,
+    };
+
+    fn count(grid: &Grid, id: u16) -> usize {
+        (0 .. grid.height() as i32)
+            .flat_map(|y| (0 .. grid.width() as i32).map(move |x| Point::new(x, y)))
+            .filter(|&p| grid[p].as_ref().map(|m| m.id()) == Some(id))
+            .count()
+    }
+
+    #[test]
+    fn eats_what_falls_in_without_breaching_stone() {
+        // A stone cup holding a layer of acid, with sand dropped in.
+        let mut grid = Grid::new(5, 6);
+        for y in 0 .. 6 {
+            grid[Point::new(0, y)] = Some(Particle::Stone(Stone));
+            grid[Point::new(4, y)] = Some(Particle::Stone(Stone));
+        }
+        for x in 1 .. 4 {
+            grid[Point::new(x, 5)] = Some(Particle::Stone(Stone));
+            grid[Point::new(x, 4)] = Some(Particle::Acid(Acid));
+        }
+        grid[Point::new(2, 0)] = Some(Particle::Sand(Sand));
+
+        for _ in 0 .. 1000 {
+            grid.update();
+        }
+
+        assert_eq!(count(&grid, Stone.id()), 15);
+        assert_eq!(count(&grid, Sand.id()), 0);
+        assert_eq!(count(&grid, Acid.id()), 2);
+    }
+}
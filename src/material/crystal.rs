@@ -0,0 +1,146 @@
+use rand::{
+    seq::SliceRandom,
+    Rng,
+    RngCore,
+};
+
+use crate::{
+    Color,
+    Grid,
+    Material,
+    Particle,
+    Point,
+    Water,
+};
+
+/// Water warmer than this never freezes onto a crystal, in degrees Celsius.
+const FREEZING_POINT: f32 = 0.0;
+
+/// Water this cold or colder freezes onto a crystal fastest.
+const COLDEST: f32 = -40.0;
+
+/// Chance per tick that a crystal grows into the coldest water, so that
+/// formations creep outwards rather than filling a pool at once.
+const GROW_CHANCE: f64 = 0.2;
+
+/// A seed that freezes the cold water around it into more of itself. It only
+/// grows into water touching no other crystal, so it branches out in
+/// fronds rather than spreading as a solid block.
+#[derive(Clone, Copy)]
+pub struct Crystal;
+
+impl Material for Crystal {
+    fn update(&self, _grid: &Grid, position: Point, _rng: &mut dyn RngCore) -> Point {
+        position
+    }
+
+    fn color(&self) -> Color {
+        Color::rgb(170, 220, 255)
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Crystal(*self)
+    }
+
+    fn id(&self) -> u16 {
+        19
+    }
+
+    fn name(&self) -> &'static str {
+        "crystal"
+    }
+
+    fn description(&self) -> &'static str {
+        "Grows branching crystals through water below freezing."
+    }
+
+    fn shade_variation(&self) -> u8 {
+        12
+    }
+
+    fn density(&self) -> f32 {
+        f32::INFINITY
+    }
+
+    fn heat_transfer(&self) -> f32 {
+        0.3
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+
+    /// Keeps growing once the water around it settles.
+    fn is_restless(&self) -> bool {
+        true
+    }
+
+    /// Grows into a neighbouring water cell that touches no other crystal,
+    /// more readily the colder it is.
+    fn spread(&self, grid: &Grid, position: Point, rng: &mut dyn RngCore) -> Option<Point> {
+        let is = |point: Point, id: u16| grid.material_at(point).is_some_and(|m| m.id() == id);
+        let neighbors = |point: Point| [
+            point.offset(0, -1),
+            point.offset(-1, 0),
+            point.offset(1, 0),
+            point.offset(0, 1),
+        ];
+
+        let tips: Vec<_> = neighbors(position).iter().copied()
+            .filter(|&neighbor| is(neighbor, Water.id()))
+            .filter(|&neighbor| neighbors(neighbor).iter().copied()
+                .all(|beyond| beyond == position || !is(beyond, self.id())))
+            .collect();
+        let tip = *tips.choose(rng)?;
+
+        let coldness = (FREEZING_POINT - grid.temperature_at(tip)) / (FREEZING_POINT - COLDEST);
+        let chance = GROW_CHANCE * coldness.clamp(0.0, 1.0) as f64;
+        if chance > 0.0 && rng.gen_bool(chance) { Some(tip) } else { None }
+    }
+
+    fn spreads_over(&self, occupant: &dyn Material) -> bool {
+        occupant.id() == Water.id()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rect;
+
+    fn count_crystals(grid: &Grid) -> usize {
+        grid.iter_filled().filter(|(_, m)| m.id() == Crystal.id()).count()
+    }
+
+    /// A pool of water at `temperature` with a crystal seed in the middle.
+    fn seeded_pool(temperature: f32) -> Grid {
+        let mut grid = Grid::with_seed(9, 9, 5);
+        grid.fill_rect(Rect::new(0, 0, 9, 9), &Water);
+        grid[Point::new(4, 4)] = Some(Particle::Crystal(Crystal));
+        for point in Rect::new(0, 0, 9, 9).points() {
+            grid.set_temperature(point, temperature);
+        }
+        grid
+    }
+
+    #[test]
+    fn grows_through_cold_water() {
+        let mut grid = seeded_pool(-30.0);
+
+        grid.run(30);
+
+        let crystals = count_crystals(&grid);
+        assert!(crystals > 1, "{} crystals", crystals);
+        // Branches leave water between them.
+        assert!(crystals < 81 / 2, "{} crystals", crystals);
+    }
+
+    #[test]
+    fn leaves_warm_water_alone() {
+        let mut grid = seeded_pool(10.0);
+
+        grid.run(30);
+
+        assert_eq!(count_crystals(&grid), 1);
+    }
+}
@@ -0,0 +1,104 @@
+use rand::RngCore;
+
+use crate::{
+    Cell,
+    Color,
+    Grid,
+    Material,
+    Particle,
+    Point,
+};
+
+/// An outlet that swallows any loose particle touching it, so that
+/// spawners can feed flows that never fill the grid.
+#[derive(Clone, Copy)]
+pub struct Drain;
+
+impl Material for Drain {
+    fn update(&self, _grid: &Grid, position: Point, _rng: &mut dyn RngCore) -> Point {
+        position
+    }
+
+    fn color(&self) -> Color {
+        Color::rgb(45, 40, 60)
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Drain(*self)
+    }
+
+    fn id(&self) -> u16 {
+        18
+    }
+
+    fn name(&self) -> &'static str {
+        "drain"
+    }
+
+    fn description(&self) -> &'static str {
+        "Swallows anything loose that touches it."
+    }
+
+    fn shade_variation(&self) -> u8 {
+        3
+    }
+
+    fn density(&self) -> f32 {
+        f32::INFINITY
+    }
+
+    fn acid_resistant(&self) -> bool {
+        true
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+
+    /// Walls and other drains next to a drain are left alone, so it can be
+    /// set into the floor.
+    fn react(&self, neighbor: &dyn Material, _rng: &mut dyn RngCore) -> Option<(Cell, Cell)> {
+        if neighbor.is_static() { return None; }
+
+        Some((Some(Particle::Drain(Drain)), None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Rect,
+        Sand,
+        Spawner,
+        Stone,
+    };
+
+    #[test]
+    fn keeps_a_stream_from_filling_the_grid() {
+        let mut grid = Grid::new(5, 10);
+        grid.fill_rect(Rect::new(0, 9, 5, 1), &Drain);
+        grid.add_spawner(Spawner::new(Rect::new(2, 0, 1, 1), &Sand, 1.0));
+
+        let mut most = 0;
+        for _ in 0 .. 500 {
+            grid.update();
+            most = most.max(grid.count_filled() - 5);
+        }
+
+        // A column's worth of grains at most are ever on their way down.
+        assert!(most <= 9, "{} grains at once", most);
+        assert_eq!(grid.iter_filled().filter(|(_, m)| m.id() == Drain.id()).count(), 5);
+    }
+
+    #[test]
+    fn leaves_walls_alone() {
+        let mut grid = Grid::new(2, 1);
+        grid[Point::new(0, 0)] = Some(Particle::Drain(Drain));
+        grid[Point::new(1, 0)] = Some(Particle::Stone(Stone));
+
+        grid.run(5);
+
+        assert_eq!(grid.count_filled(), 2);
+    }
+}
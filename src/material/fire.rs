@@ -0,0 +1,164 @@
+use rand::{
+    Rng,
+    RngCore,
+};
+
+use crate::{
+    Cell,
+    Color,
+    Grid,
+    Material,
+    Particle,
+    Point,
+    Smoke,
+};
+
+/// How many ticks a flame burns before going out in a puff of smoke.
+const LIFE: u16 = 40;
+
+/// Chance per tick that a flame sets a flammable neighbour alight.
+const IGNITE_CHANCE: f64 = 0.2;
+
+/// Degrees a flame adds to its cell each tick.
+const HEAT: f32 = 25.0;
+
+/// A short-lived flame that spreads to flammable neighbours.
+#[derive(Clone, Copy)]
+pub struct Fire {
+    life: u16,
+    /// Re-rolled every tick to pick a shade between orange and yellow.
+    flicker: u8,
+}
+
+impl Fire {
+    pub fn new() -> Fire {
+        Fire {
+            life: LIFE,
+            flicker: 0,
+        }
+    }
+}
+
+impl Default for Fire {
+    fn default() -> Fire {
+        Fire::new()
+    }
+}
+
+impl Material for Fire {
+    fn update(&self, _grid: &Grid, position: Point, _rng: &mut dyn RngCore) -> Point {
+        position
+    }
+
+    fn color(&self) -> Color {
+        // Blend from orange towards yellow.
+        let green = 100 + (self.flicker as u16 * 120 / u8::MAX as u16) as u8;
+        Color::rgb(255, green, 20)
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Fire(*self)
+    }
+
+    fn id(&self) -> u16 {
+        6
+    }
+
+    fn name(&self) -> &'static str {
+        "fire"
+    }
+
+    fn description(&self) -> &'static str {
+        "Burns briefly, spreading to anything flammable, and leaves smoke."
+    }
+
+    fn density(&self) -> f32 {
+        0.0
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+
+    fn heat_output(&self) -> f32 {
+        HEAT
+    }
+
+    fn is_restless(&self) -> bool {
+        true
+    }
+
+    fn tick(&mut self, rng: &mut dyn RngCore) -> Option<Cell> {
+        self.flicker = rng.gen();
+        None
+    }
+
+    fn life(&mut self) -> Option<&mut u16> {
+        Some(&mut self.life)
+    }
+
+    /// Goes out in a puff of smoke.
+    fn expire(&self) -> Cell {
+        Some(Particle::Smoke(Smoke::new()))
+    }
+
+    fn react(
+        &self,
+        neighbor: &dyn Material,
+        rng: &mut dyn RngCore,
+    ) -> Option<(Cell, Cell)> {
+        if neighbor.flammable() && rng.gen_bool(IGNITE_CHANCE) {
+            Some((Some(Particle::Fire(*self)), Some(Particle::Fire(Fire::new()))))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Wood;
+
+    fn id_at(grid: &Grid, x: i32, y: i32) -> Option<u16> {
+        grid[Point::new(x, y)].as_ref().map(|m| m.id())
+    }
+
+    #[test]
+    fn ignites_flammable_neighbours() {
+        let mut grid = Grid::new(3, 1);
+        grid[Point::new(0, 0)] = Some(Particle::Fire(Fire::new()));
+        grid[Point::new(1, 0)] = Some(Particle::Wood(Wood));
+
+        for _ in 0 .. 30 {
+            grid.update();
+        }
+
+        assert_eq!(id_at(&grid, 1, 0), Some(Fire::new().id()));
+    }
+
+    #[test]
+    fn spares_other_materials() {
+        let mut grid = Grid::new(2, 1);
+        grid[Point::new(0, 0)] = Some(Particle::Fire(Fire::new()));
+        grid[Point::new(1, 0)] = Some(Particle::Stone(crate::Stone));
+
+        for _ in 0 .. 30 {
+            grid.update();
+        }
+
+        assert_eq!(id_at(&grid, 1, 0), Some(crate::Stone.id()));
+    }
+
+    #[test]
+    fn burns_out_into_smoke() {
+        let mut grid = Grid::new(1, 1);
+        grid[Point::new(0, 0)] = Some(Particle::Fire(Fire::new()));
+
+        for _ in 0 ..= LIFE {
+            grid.update();
+        }
+
+        assert_eq!(id_at(&grid, 0, 0), Some(Smoke::new().id()));
+    }
+}
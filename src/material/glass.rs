@@ -0,0 +1,74 @@
+use rand::RngCore;
+
+use crate::{
+    Color,
+    Grid,
+    Material,
+    Particle,
+    Point,
+};
+
+/// Sand fused by great heat into a clear, solid pane.
+#[derive(Clone, Copy)]
+pub struct Glass;
+
+impl Material for Glass {
+    fn update(&self, _grid: &Grid, position: Point, _rng: &mut dyn RngCore) -> Point {
+        position
+    }
+
+    fn color(&self) -> Color {
+        Color::rgb(175, 225, 230)
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Glass(*self)
+    }
+
+    fn id(&self) -> u16 {
+        13
+    }
+
+    fn name(&self) -> &'static str {
+        "glass"
+    }
+
+    fn description(&self) -> &'static str {
+        "Solid left behind when sand melts."
+    }
+
+    fn shade_variation(&self) -> u8 {
+        10
+    }
+
+    fn density(&self) -> f32 {
+        f32::INFINITY
+    }
+
+    fn acid_resistant(&self) -> bool {
+        true
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Acid;
+
+    #[test]
+    fn holds_acid() {
+        let mut grid = Grid::new(3, 2);
+        for x in 0 .. 3 {
+            grid[Point::new(x, 1)] = Some(Particle::Glass(Glass));
+        }
+        grid[Point::new(1, 0)] = Some(Particle::Acid(Acid));
+
+        grid.run(500);
+
+        assert!((0 .. 3).all(|x| grid[Point::new(x, 1)].as_ref().map(|m| m.id()) == Some(Glass.id())));
+    }
+}
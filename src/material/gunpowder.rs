@@ -0,0 +1,102 @@
+use rand::RngCore;
+
+use crate::{
+    material,
+    Color,
+    Grid,
+    Material,
+    Particle,
+    Point,
+};
+
+/// How far an exploding grain's blast reaches, in cells.
+const BLAST_RADIUS: i32 = 3;
+
+/// A loose powder that explodes when it catches fire.
+#[derive(Clone, Copy)]
+pub struct Gunpowder;
+
+impl Material for Gunpowder {
+    fn update(&self, grid: &Grid, position: Point, rng: &mut dyn RngCore) -> Point {
+        material::pile(grid, position, self, rng)
+    }
+
+    fn color(&self) -> Color {
+        Color::rgb(60, 60, 65)
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Gunpowder(*self)
+    }
+
+    fn id(&self) -> u16 {
+        15
+    }
+
+    fn name(&self) -> &'static str {
+        "gunpowder"
+    }
+
+    fn description(&self) -> &'static str {
+        "Loose powder that explodes when lit."
+    }
+
+    fn shade_variation(&self) -> u8 {
+        10
+    }
+
+    fn density(&self) -> f32 {
+        1.6
+    }
+
+    fn wind_response(&self) -> f32 {
+        0.1
+    }
+
+    fn flammable(&self) -> bool {
+        true
+    }
+
+    fn blast_radius(&self) -> Option<i32> {
+        Some(BLAST_RADIUS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Fire,
+        Stone,
+    };
+
+    #[test]
+    fn igniting_one_grain_clears_its_neighbours() {
+        let mut grid = Grid::new(9, 9);
+        grid.paint_circle(Point::new(4, 4), 1, &Stone);
+        grid[Point::new(4, 4)] = Some(Particle::Gunpowder(Gunpowder));
+        grid[Point::new(3, 3)] = Some(Particle::Fire(Fire::new()));
+        grid[Point::new(5, 5)] = Some(Particle::Gunpowder(Gunpowder));
+
+        grid.update();
+
+        // The stone walls hold, but everything loose nearby is gone.
+        assert_eq!(grid[Point::new(4, 3)].as_ref().map(|m| m.id()), Some(Stone.id()));
+        assert!(grid[Point::new(4, 4)].as_ref().is_none_or(|m| m.id() != Gunpowder.id()));
+        assert!(grid[Point::new(5, 5)].is_none());
+    }
+
+    #[test]
+    fn explosions_set_off_more_gunpowder() {
+        let mut grid = Grid::new(40, 2);
+        for x in 0 .. 40 {
+            grid[Point::new(x, 1)] = Some(Particle::Gunpowder(Gunpowder));
+        }
+        grid[Point::new(0, 0)] = Some(Particle::Fire(Fire::new()));
+
+        grid.run(100);
+
+        let left = grid.iter_filled().filter(|(_, m)| m.id() == Gunpowder.id()).count();
+        assert_eq!(left, 0);
+    }
+}
@@ -0,0 +1,111 @@
+use rand::RngCore;
+
+use crate::{
+    Color,
+    Grid,
+    Material,
+    Particle,
+    Point,
+    Water,
+};
+
+/// Temperature above which ice melts, in degrees Celsius.
+const MELTING_POINT: f32 = 0.0;
+
+/// How cold freshly placed ice is.
+const TEMPERATURE: f32 = -20.0;
+
+/// Frozen water that holds its shape until warmed past its melting point.
+#[derive(Clone, Copy)]
+pub struct Ice;
+
+impl Material for Ice {
+    fn update(&self, _grid: &Grid, position: Point, _rng: &mut dyn RngCore) -> Point {
+        position
+    }
+
+    fn color(&self) -> Color {
+        Color::rgb(200, 230, 250)
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Ice(*self)
+    }
+
+    fn id(&self) -> u16 {
+        9
+    }
+
+    fn name(&self) -> &'static str {
+        "ice"
+    }
+
+    fn description(&self) -> &'static str {
+        "Solid water that melts when warmed."
+    }
+
+    fn shade_variation(&self) -> u8 {
+        6
+    }
+
+    fn density(&self) -> f32 {
+        f32::INFINITY
+    }
+
+    fn heat_transfer(&self) -> f32 {
+        0.3
+    }
+
+    fn spawn_temperature(&self) -> Option<f32> {
+        Some(TEMPERATURE)
+    }
+
+    fn phase_change(&self, temperature: f32) -> Option<Particle> {
+        if temperature > MELTING_POINT {
+            Some(Particle::Water(Water))
+        } else {
+            None
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Fire;
+
+    fn is_water(grid: &Grid, point: Point) -> bool {
+        grid[point].as_ref().map(|m| m.id()) == Some(Water.id())
+    }
+
+    #[test]
+    fn melts_above_its_melting_point() {
+        let mut grid = Grid::new(1, 1);
+        grid.paint_circle(Point::new(0, 0), 0, &Ice);
+        grid.update();
+        assert!(!is_water(&grid, Point::new(0, 0)));
+
+        grid.set_temperature(Point::new(0, 0), 5.0);
+        grid.update();
+
+        assert!(is_water(&grid, Point::new(0, 0)));
+    }
+
+    #[test]
+    fn nearby_fire_melts_it() {
+        let mut grid = Grid::new(3, 1);
+        grid.paint_circle(Point::new(0, 0), 0, &Ice);
+        grid[Point::new(1, 0)] = Some(Particle::Fire(Fire::new()));
+
+        for _ in 0 .. 40 {
+            grid.update();
+        }
+
+        // The meltwater is free to flow off, so just check it's all gone.
+        assert!((0 .. 3).all(|x| grid[Point::new(x, 0)].as_ref().map(|m| m.id()) != Some(Ice.id())));
+    }
+}
@@ -0,0 +1,169 @@
+use rand::{
+    Rng,
+    RngCore,
+};
+
+use crate::{
+    material,
+    Cell,
+    Color,
+    Grid,
+    Material,
+    Particle,
+    Point,
+    Steam,
+    Stone,
+    Water,
+};
+
+/// Chance per tick that lava moves at all, making it much slower than water.
+/// Once down, its [`Material::viscosity`] slows its spread further.
+const FLOW_CHANCE: f64 = 0.3;
+
+const VISCOSITY: u8 = 200;
+
+/// Chance per tick that lava melts a neighbouring stone into more lava.
+const MELT_CHANCE: f64 = 0.002;
+
+/// How hot freshly placed lava is.
+const TEMPERATURE: f32 = 1200.0;
+
+/// Below this temperature lava has dimmed all the way to `COOL_COLOR`.
+const COOL_TEMPERATURE: f32 = 500.0;
+
+const COOL_COLOR: Color = Color::rgb(120, 25, 10);
+
+/// Molten rock: a slow, heavy liquid that slowly eats into stone.
+#[derive(Clone, Copy)]
+pub struct Lava;
+
+impl Material for Lava {
+    fn update(&self, grid: &Grid, position: Point, rng: &mut dyn RngCore) -> Point {
+        if !rng.gen_bool(FLOW_CHANCE) {
+            return position;
+        }
+
+        material::flow(grid, position, self, rng)
+    }
+
+    fn color(&self) -> Color {
+        Color::rgb(230, 80, 20)
+    }
+
+    /// Glows brightest when freshly placed, dimming to a dull red as it
+    /// cools.
+    fn heated_color(&self, temperature: f32) -> Color {
+        let heat = ((temperature - COOL_TEMPERATURE) / (TEMPERATURE - COOL_TEMPERATURE))
+            .clamp(0.0, 1.0);
+        let blend = |cool: u8, hot: u8| (cool as f32 + (hot as f32 - cool as f32) * heat) as u8;
+
+        let hot = self.color();
+        Color::rgb(blend(COOL_COLOR.r, hot.r), blend(COOL_COLOR.g, hot.g), blend(COOL_COLOR.b, hot.b))
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Lava(*self)
+    }
+
+    fn id(&self) -> u16 {
+        4
+    }
+
+    fn name(&self) -> &'static str {
+        "lava"
+    }
+
+    fn description(&self) -> &'static str {
+        "Molten rock that sets things alight until water turns it to stone."
+    }
+
+    fn density(&self) -> f32 {
+        3.0
+    }
+
+    fn viscosity(&self) -> u8 {
+        VISCOSITY
+    }
+
+    fn spawn_temperature(&self) -> Option<f32> {
+        Some(TEMPERATURE)
+    }
+
+    /// Keeps lava awake while it creeps along, and repainted as it cools.
+    fn is_restless(&self) -> bool {
+        true
+    }
+
+    /// Touching water quenches lava into stone and boils the water off,
+    /// while stone left touching lava long enough melts.
+    fn react(
+        &self,
+        neighbor: &dyn Material,
+        rng: &mut dyn RngCore,
+    ) -> Option<(Cell, Cell)> {
+        if neighbor.id() == Water.id() {
+            Some((Some(Particle::Stone(Stone)), Some(Particle::Steam(Steam))))
+        } else if neighbor.id() == Stone.id() && rng.gen_bool(MELT_CHANCE) {
+            Some((Some(Particle::Lava(Lava)), Some(Particle::Lava(Lava))))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rect;
+
+    #[test]
+    fn water_quenches_lava_into_stone() {
+        let mut grid = Grid::new(2, 2);
+        grid[Point::new(0, 1)] = Some(Particle::Lava(Lava));
+        grid[Point::new(1, 1)] = Some(Particle::Water(Water));
+
+        grid.update();
+
+        let id_at = |x, y| grid[Point::new(x, y)].as_ref().map(|m| m.id());
+        assert_eq!(id_at(0, 1), Some(Stone.id()));
+        // The steam has already started to rise.
+        assert_eq!(id_at(1, 0), Some(Steam.id()));
+        assert_eq!(id_at(1, 1), None);
+    }
+
+    #[test]
+    fn melts_stone_it_rests_on() {
+        let mut grid = Grid::new(1, 2);
+        grid.paint_circle(Point::new(0, 0), 0, &Lava);
+        grid[Point::new(0, 1)] = Some(Particle::Stone(Stone));
+
+        for _ in 0 .. 5000 {
+            grid.update();
+        }
+
+        assert!((0 .. 2).all(|y| grid[Point::new(0, y)].as_ref().map(|m| m.id()) == Some(Lava.id())));
+    }
+
+    #[test]
+    fn flows_slower_than_water() {
+        let fallen = |material: &dyn Material| {
+            let mut grid = Grid::new(1, 20);
+            grid.fill_rect(Rect::new(0, 0, 1, 1), material);
+            for _ in 0 .. 10 {
+                grid.update();
+            }
+
+            (0 .. 20).find(|&y| grid[Point::new(0, y)].is_some())
+        };
+
+        assert!(fallen(&Lava) < fallen(&Water));
+    }
+
+    #[test]
+    fn dims_as_it_cools() {
+        let brightness = |color: Color| color.r as u32 + color.g as u32 + color.b as u32;
+
+        assert!(brightness(Lava.heated_color(TEMPERATURE)) > brightness(Lava.heated_color(20.0)));
+        assert_eq!(Lava.heated_color(TEMPERATURE), Lava.color());
+    }
+}
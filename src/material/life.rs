@@ -0,0 +1,48 @@
+use rand::RngCore;
+
+use crate::{
+    Color,
+    Grid,
+    Material,
+    Particle,
+    Point,
+};
+
+/// A live cell for [`Mode::Life`](crate::Mode::Life). Physics leaves it
+/// where it is; only the rules of Life make it appear and vanish.
+#[derive(Clone, Copy)]
+pub struct Life;
+
+impl Material for Life {
+    fn update(&self, _grid: &Grid, position: Point, _rng: &mut dyn RngCore) -> Point {
+        position
+    }
+
+    fn color(&self) -> Color {
+        Color::rgb(235, 235, 235)
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Life(*self)
+    }
+
+    fn id(&self) -> u16 {
+        12
+    }
+
+    fn name(&self) -> &'static str {
+        "life"
+    }
+
+    fn description(&self) -> &'static str {
+        "A cell in Conway's Game of Life."
+    }
+
+    fn density(&self) -> f32 {
+        f32::INFINITY
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,60 @@
+use rand::RngCore;
+
+use crate::{
+    Color,
+    Grid,
+    Material,
+    Particle,
+    Point,
+};
+
+/// A solid that carries electric charge along itself, see
+/// [`Grid::electrify`].
+#[derive(Clone, Copy)]
+pub struct Metal;
+
+impl Material for Metal {
+    fn update(&self, _grid: &Grid, position: Point, _rng: &mut dyn RngCore) -> Point {
+        position
+    }
+
+    fn color(&self) -> Color {
+        Color::rgb(150, 160, 175)
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Metal(*self)
+    }
+
+    fn id(&self) -> u16 {
+        16
+    }
+
+    fn name(&self) -> &'static str {
+        "metal"
+    }
+
+    fn description(&self) -> &'static str {
+        "Solid that conducts heat and electricity."
+    }
+
+    fn shade_variation(&self) -> u8 {
+        4
+    }
+
+    fn density(&self) -> f32 {
+        f32::INFINITY
+    }
+
+    fn heat_transfer(&self) -> f32 {
+        0.9
+    }
+
+    fn conducts(&self) -> bool {
+        true
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,185 @@
+use rand::{
+    Rng,
+    RngCore,
+};
+
+use crate::{
+    material,
+    Cell,
+    Color,
+    Grid,
+    Material,
+    Particle,
+    Point,
+    Sand,
+    Water,
+};
+
+/// Ticks mud lasts away from water before drying back into sand.
+const MOISTURE: u16 = 300;
+
+/// Chance per tick that water touching mud soaks it through again, so mud
+/// beside water never dries out.
+const SOAK_CHANCE: f64 = 0.1;
+
+/// Temperature above which mud bakes dry at once, in degrees Celsius.
+const DRYING_POINT: f32 = 100.0;
+
+/// Wet grains cling together, so mud barely slides off anything.
+const REPOSE: u8 = 240;
+
+/// Wet sand, made where sand sits in water. It slumps into steep heaps and
+/// dries back into sand once it has been away from water for a while.
+#[derive(Clone, Copy)]
+pub struct Mud {
+    /// Ticks left before the mud dries out.
+    moisture: u16,
+}
+
+impl Mud {
+    pub fn new() -> Mud {
+        Mud { moisture: MOISTURE }
+    }
+}
+
+impl Default for Mud {
+    fn default() -> Mud {
+        Mud::new()
+    }
+}
+
+impl Material for Mud {
+    fn update(&self, grid: &Grid, position: Point, rng: &mut dyn RngCore) -> Point {
+        material::pile(grid, position, self, rng)
+    }
+
+    fn color(&self) -> Color {
+        Color::rgb(100, 70, 40)
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Mud(*self)
+    }
+
+    fn id(&self) -> u16 {
+        20
+    }
+
+    fn name(&self) -> &'static str {
+        "mud"
+    }
+
+    fn description(&self) -> &'static str {
+        "Sand soaked through with water, heavy and sticky until it dries."
+    }
+
+    fn shade_variation(&self) -> u8 {
+        10
+    }
+
+    /// Heavier than the sand it was made from, so it settles beneath it.
+    fn density(&self) -> f32 {
+        2.5
+    }
+
+    fn repose(&self) -> u8 {
+        REPOSE
+    }
+
+    fn heat_transfer(&self) -> f32 {
+        0.4
+    }
+
+    fn phase_change(&self, temperature: f32) -> Option<Particle> {
+        if temperature > DRYING_POINT {
+            Some(Particle::Sand(Sand))
+        } else {
+            None
+        }
+    }
+
+    /// Resting mud goes on drying while nothing wets it.
+    fn is_unsettled(&self, grid: &Grid, position: Point) -> bool {
+        !material::touching(grid, position, Water.id())
+    }
+
+    fn life(&mut self) -> Option<&mut u16> {
+        Some(&mut self.moisture)
+    }
+
+    /// Dries out into sand.
+    fn expire(&self) -> Cell {
+        Some(Particle::Sand(Sand))
+    }
+
+    fn react(&self, neighbor: &dyn Material, rng: &mut dyn RngCore) -> Option<(Cell, Cell)> {
+        if neighbor.id() != Water.id() || !rng.gen_bool(SOAK_CHANCE) { return None; }
+
+        Some((Some(Particle::Mud(Mud::new())), Some(neighbor.particle())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_mud(grid: &Grid, point: Point) -> bool {
+        grid[point].as_ref().map(|m| m.id()) == Some(Mud::new().id())
+    }
+
+    #[test]
+    fn sand_beside_water_turns_to_mud() {
+        let mut grid = Grid::with_seed(2, 1, 1);
+        grid[Point::new(0, 0)] = Some(Particle::Sand(Sand));
+        grid[Point::new(1, 0)] = Some(Particle::Water(Water));
+
+        // Well past the few hundred ticks a grain takes on average, though
+        // both cells fall asleep almost at once.
+        grid.run(3000);
+
+        assert!(is_mud(&grid, Point::new(0, 0)));
+    }
+
+    #[test]
+    fn a_sunken_bed_turns_to_mud_and_settles() {
+        // A bed of sand under a pool.
+        let width = 8;
+        let mut grid = Grid::with_seed(width, 2, 1);
+        for x in 0 .. width as i32 {
+            grid[Point::new(x, 0)] = Some(Particle::Water(Water));
+            grid[Point::new(x, 1)] = Some(Particle::Sand(Sand));
+        }
+
+        assert!(grid.run_until_settled(5000).is_some());
+
+        assert!((0 .. width as i32).all(|x| is_mud(&grid, Point::new(x, 1))));
+        // The water is still there, keeping the mud wet.
+        grid.run(2 * MOISTURE as usize);
+        assert!((0 .. width as i32).all(|x| is_mud(&grid, Point::new(x, 1))));
+        assert_eq!(grid.count_filled(), 2 * width);
+        assert!(grid.is_settled());
+    }
+
+    #[test]
+    fn dries_back_into_sand_away_from_water() {
+        let mut grid = Grid::new(1, 1);
+        grid[Point::new(0, 0)] = Some(Particle::Mud(Mud::new()));
+
+        grid.run(MOISTURE as usize / 2);
+        assert!(is_mud(&grid, Point::new(0, 0)));
+
+        grid.run(MOISTURE as usize);
+        assert_eq!(grid[Point::new(0, 0)].as_ref().map(|m| m.id()), Some(Sand.id()));
+    }
+
+    #[test]
+    fn bakes_dry_when_heated() {
+        let mut grid = Grid::new(1, 1);
+        grid[Point::new(0, 0)] = Some(Particle::Mud(Mud::new()));
+
+        grid.set_temperature(Point::new(0, 0), DRYING_POINT + 50.0);
+        grid.update();
+
+        assert_eq!(grid[Point::new(0, 0)].as_ref().map(|m| m.id()), Some(Sand.id()));
+    }
+}
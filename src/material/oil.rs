@@ -0,0 +1,131 @@
+use rand::RngCore;
+
+use crate::{
+    material,
+    Color,
+    Fire,
+    Grid,
+    Material,
+    Particle,
+    Point,
+};
+
+/// Temperature at which oil catches fire by itself, in degrees Celsius.
+const IGNITION_POINT: f32 = 250.0;
+
+/// Thicker than water, so a spill spreads more slowly.
+const VISCOSITY: u8 = 100;
+
+/// A flammable liquid light enough to float on water.
+#[derive(Clone, Copy)]
+pub struct Oil;
+
+impl Material for Oil {
+    fn update(&self, grid: &Grid, position: Point, rng: &mut dyn RngCore) -> Point {
+        material::flow(grid, position, self, rng)
+    }
+
+    fn color(&self) -> Color {
+        Color::rgb(45, 32, 20)
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Oil(*self)
+    }
+
+    fn id(&self) -> u16 {
+        11
+    }
+
+    fn name(&self) -> &'static str {
+        "oil"
+    }
+
+    fn description(&self) -> &'static str {
+        "Flammable liquid that floats on water."
+    }
+
+    fn shade_variation(&self) -> u8 {
+        4
+    }
+
+    fn density(&self) -> f32 {
+        0.8
+    }
+
+    fn viscosity(&self) -> u8 {
+        VISCOSITY
+    }
+
+    fn heat_transfer(&self) -> f32 {
+        0.3
+    }
+
+    fn phase_change(&self, temperature: f32) -> Option<Particle> {
+        if temperature >= IGNITION_POINT {
+            Some(Particle::Fire(Fire::new()))
+        } else {
+            None
+        }
+    }
+
+    fn flammable(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Water;
+
+    fn id_at(grid: &Grid, x: i32, y: i32) -> Option<u16> {
+        grid[Point::new(x, y)].as_ref().map(|m| m.id())
+    }
+
+    #[test]
+    fn rises_through_water() {
+        let mut grid = Grid::new(1, 3);
+        grid[Point::new(0, 0)] = Some(Particle::Water(Water));
+        grid[Point::new(0, 1)] = Some(Particle::Water(Water));
+        grid[Point::new(0, 2)] = Some(Particle::Oil(Oil));
+
+        for _ in 0 .. 5 {
+            grid.update();
+        }
+
+        assert_eq!(id_at(&grid, 0, 0), Some(Oil.id()));
+        assert_eq!(id_at(&grid, 0, 1), Some(Water.id()));
+        assert_eq!(id_at(&grid, 0, 2), Some(Water.id()));
+    }
+
+    #[test]
+    fn spreads_slower_than_water() {
+        let spread = |liquid: &dyn Material| {
+            let mut grid = Grid::with_seed(41, 12, 7);
+            for y in 2 .. 12 {
+                grid[Point::new(20, y)] = Some(liquid.particle());
+            }
+            grid.run(20);
+
+            let xs: Vec<_> = grid.iter_filled().map(|(point, _)| point.x).collect();
+            xs.iter().max().unwrap() - xs.iter().min().unwrap()
+        };
+
+        assert!(spread(&Oil) < spread(&Water), "{} vs {}", spread(&Oil), spread(&Water));
+    }
+
+    #[test]
+    fn burns_away() {
+        let mut grid = Grid::new(3, 1);
+        grid[Point::new(0, 0)] = Some(Particle::Fire(Fire::new()));
+        grid[Point::new(1, 0)] = Some(Particle::Oil(Oil));
+        grid[Point::new(2, 0)] = Some(Particle::Oil(Oil));
+
+        for _ in 0 .. 500 {
+            grid.update();
+        }
+
+        assert!((0 .. 3).all(|x| id_at(&grid, x, 0) != Some(Oil.id())));
+    }
+}
@@ -0,0 +1,136 @@
+use std::ops::{
+    Deref,
+    DerefMut,
+};
+
+use super::{
+    Acid,
+    Crystal,
+    Drain,
+    Fire,
+    Glass,
+    Gunpowder,
+    Ice,
+    Lava,
+    Life,
+    Material,
+    Metal,
+    Mud,
+    Oil,
+    Plant,
+    Sand,
+    Smoke,
+    Steam,
+    Stone,
+    Torch,
+    Water,
+    Wood,
+};
+
+/// A particle of any material, held by value. Each variant carries its
+/// material along with whatever state that keeps, such as a fire's
+/// remaining life, and derefs to it as a [`Material`], so a particle is
+/// used just like the material it holds. Copying one never allocates.
+#[derive(Clone, Copy)]
+pub enum Particle {
+    Sand(Sand),
+    Water(Water),
+    Stone(Stone),
+    Lava(Lava),
+    Steam(Steam),
+    Fire(Fire),
+    Wood(Wood),
+    Smoke(Smoke),
+    Ice(Ice),
+    Acid(Acid),
+    Oil(Oil),
+    Life(Life),
+    Glass(Glass),
+    Plant(Plant),
+    Gunpowder(Gunpowder),
+    Metal(Metal),
+    Torch(Torch),
+    Drain(Drain),
+    Crystal(Crystal),
+    Mud(Mud),
+}
+
+impl Deref for Particle {
+    type Target = dyn Material;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Particle::Sand(material) => material,
+            Particle::Water(material) => material,
+            Particle::Stone(material) => material,
+            Particle::Lava(material) => material,
+            Particle::Steam(material) => material,
+            Particle::Fire(material) => material,
+            Particle::Wood(material) => material,
+            Particle::Smoke(material) => material,
+            Particle::Ice(material) => material,
+            Particle::Acid(material) => material,
+            Particle::Oil(material) => material,
+            Particle::Life(material) => material,
+            Particle::Glass(material) => material,
+            Particle::Plant(material) => material,
+            Particle::Gunpowder(material) => material,
+            Particle::Metal(material) => material,
+            Particle::Torch(material) => material,
+            Particle::Drain(material) => material,
+            Particle::Crystal(material) => material,
+            Particle::Mud(material) => material,
+        }
+    }
+}
+
+impl DerefMut for Particle {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            Particle::Sand(material) => material,
+            Particle::Water(material) => material,
+            Particle::Stone(material) => material,
+            Particle::Lava(material) => material,
+            Particle::Steam(material) => material,
+            Particle::Fire(material) => material,
+            Particle::Wood(material) => material,
+            Particle::Smoke(material) => material,
+            Particle::Ice(material) => material,
+            Particle::Acid(material) => material,
+            Particle::Oil(material) => material,
+            Particle::Life(material) => material,
+            Particle::Glass(material) => material,
+            Particle::Plant(material) => material,
+            Particle::Gunpowder(material) => material,
+            Particle::Metal(material) => material,
+            Particle::Torch(material) => material,
+            Particle::Drain(material) => material,
+            Particle::Crystal(material) => material,
+            Particle::Mud(material) => material,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+
+    use crate::{
+        material,
+        Cell,
+    };
+
+    #[test]
+    fn a_cell_takes_a_few_bytes() {
+        assert!(mem::size_of::<Cell>() <= 8, "{} bytes", mem::size_of::<Cell>());
+    }
+
+    #[test]
+    fn acts_as_the_material_it_holds() {
+        for id in 1 ..= 20 {
+            let particle = material::from_id(id).expect("every id up to 20 is a material");
+            assert_eq!(particle.id(), id);
+            assert_eq!(particle.particle().id(), id);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use rand::{
+    seq::SliceRandom,
+    Rng,
+    RngCore,
+};
+
+use crate::{
+    grid::NEIGHBOURS,
+    material,
+    Color,
+    Grid,
+    Material,
+    Particle,
+    Point,
+    Water,
+};
+
+/// Chance per tick that a plant touching water grows into a free neighbour.
+const GROW_CHANCE: f64 = 0.01;
+
+/// Moss that creeps along wet surfaces, one cell at a time.
+#[derive(Clone, Copy)]
+pub struct Plant;
+
+impl Material for Plant {
+    fn update(&self, _grid: &Grid, position: Point, _rng: &mut dyn RngCore) -> Point {
+        position
+    }
+
+    fn color(&self) -> Color {
+        Color::rgb(60, 160, 50)
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Plant(*self)
+    }
+
+    fn id(&self) -> u16 {
+        14
+    }
+
+    fn name(&self) -> &'static str {
+        "plant"
+    }
+
+    fn description(&self) -> &'static str {
+        "Grows slowly wherever it can reach water."
+    }
+
+    fn shade_variation(&self) -> u8 {
+        12
+    }
+
+    fn density(&self) -> f32 {
+        f32::INFINITY
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+
+    fn flammable(&self) -> bool {
+        true
+    }
+
+    /// A plant beside still water goes on growing while there is room, so
+    /// only a dry or hemmed-in one lets the grid settle.
+    fn is_unsettled(&self, grid: &Grid, position: Point) -> bool {
+        material::touching(grid, position, Water.id()) &&
+        free_neighbors(grid, position).next().is_some()
+    }
+
+    /// Only grows while touching water, so it follows wet surfaces instead
+    /// of filling the grid.
+    fn spread(&self, grid: &Grid, position: Point, rng: &mut dyn RngCore) -> Option<Point> {
+        if !material::touching(grid, position, Water.id()) || !rng.gen_bool(GROW_CHANCE) {
+            return None;
+        }
+
+        let free: Vec<_> = free_neighbors(grid, position).collect();
+        free.choose(rng).copied()
+    }
+}
+
+/// The empty cells beside `position`.
+fn free_neighbors(grid: &Grid, position: Point) -> impl Iterator<Item = Point> + '_ {
+    NEIGHBOURS.iter()
+        .map(move |&(dx, dy)| position.offset(dx, dy))
+        .filter(move |&neighbor| matches!(grid.get(neighbor), Some(None)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Stone;
+
+    fn is_plant(grid: &Grid, point: Point) -> bool {
+        grid[point].as_ref().map(|m| m.id()) == Some(Plant.id())
+    }
+
+    #[test]
+    fn grows_beside_water() {
+        let mut grid = Grid::new(2, 2);
+        grid[Point::new(0, 1)] = Some(Particle::Water(Water));
+        grid[Point::new(1, 1)] = Some(Particle::Plant(Plant));
+
+        grid.run(1000);
+
+        assert!(is_plant(&grid, Point::new(1, 0)));
+        // That sprout isn't touching water, so it grows no further.
+        assert!(grid[Point::new(0, 0)].is_none());
+        // Nor has the wet plant room left to grow into.
+        assert!(grid.is_settled());
+    }
+
+    #[test]
+    fn does_not_grow_when_dry() {
+        let mut grid = Grid::new(3, 3);
+        grid[Point::new(1, 1)] = Some(Particle::Plant(Plant));
+        grid[Point::new(1, 2)] = Some(Particle::Stone(Stone));
+
+        grid.run(1000);
+
+        assert_eq!(grid.count_filled(), 2);
+        assert!(grid.is_settled());
+    }
+}
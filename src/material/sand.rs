@@ -0,0 +1,281 @@
+use rand::{
+    Rng,
+    RngCore,
+};
+
+use crate::{
+    material,
+    Cell,
+    Color,
+    Glass,
+    Grid,
+    Material,
+    Mud,
+    Particle,
+    Point,
+    Water,
+};
+
+/// Temperature above which sand fuses into glass, in degrees Celsius.
+const FUSING_POINT: f32 = 1000.0;
+
+/// Enough grip between grains that sand stands in mounds rather than
+/// slumping into flat 45 degree cones.
+const REPOSE: u8 = 160;
+
+/// Chance per tick that sand touching water soaks it up into mud. Low
+/// enough that sand poured into a pool is seen sinking through it as sand,
+/// with the bed it settles into turning to mud over the next while.
+const WETTING_CHANCE: f64 = 0.002;
+
+#[derive(Clone, Copy)]
+pub struct Sand;
+
+impl Material for Sand {
+    fn update(&self, grid: &Grid, position: Point, rng: &mut dyn RngCore) -> Point {
+        material::pile(grid, position, self, rng)
+    }
+
+    fn color(&self) -> Color {
+        Color::rgb(198, 178, 128)
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Sand(*self)
+    }
+
+    fn id(&self) -> u16 {
+        1
+    }
+
+    fn name(&self) -> &'static str {
+        "sand"
+    }
+
+    fn description(&self) -> &'static str {
+        "Falls and piles up, melting into glass when very hot."
+    }
+
+    fn shade_variation(&self) -> u8 {
+        15
+    }
+
+    fn density(&self) -> f32 {
+        2.0
+    }
+
+    fn repose(&self) -> u8 {
+        REPOSE
+    }
+
+    /// Heavy grains barely stir in the wind.
+    fn wind_response(&self) -> f32 {
+        0.1
+    }
+
+    fn heat_transfer(&self) -> f32 {
+        0.2
+    }
+
+    fn phase_change(&self, temperature: f32) -> Option<Particle> {
+        if temperature > FUSING_POINT {
+            Some(Particle::Glass(Glass))
+        } else {
+            None
+        }
+    }
+
+    /// Sand lying in water may still soak through.
+    fn is_unsettled(&self, grid: &Grid, position: Point) -> bool {
+        material::touching(grid, position, Water.id())
+    }
+
+    /// The water is left as it is, wetting more sand beside it.
+    fn react(&self, neighbor: &dyn Material, rng: &mut dyn RngCore) -> Option<(Cell, Cell)> {
+        if neighbor.id() != Water.id() || !rng.gen_bool(WETTING_CHANCE) { return None; }
+
+        Some((Some(Particle::Mud(Mud::new())), Some(neighbor.particle())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Gunpowder,
+        Stone,
+    };
+
+    fn is_sand(grid: &Grid, point: Point) -> bool {
+        grid.get(point).and_then(|cell| cell.as_ref()).map(|m| m.id()) == Some(Sand.id())
+    }
+
+    #[test]
+    fn is_named_sand() {
+        assert_eq!(Sand.name(), "sand");
+        assert!(!Sand.description().is_empty());
+    }
+
+    #[test]
+    fn falls_into_empty_space() {
+        let mut grid = Grid::new(3, 3);
+        grid[Point::new(1, 0)] = Some(Particle::Sand(Sand));
+
+        grid.update();
+
+        assert!(is_sand(&grid, Point::new(1, 1)));
+        assert!(!is_sand(&grid, Point::new(1, 0)));
+    }
+
+    #[test]
+    fn rests_on_the_floor() {
+        let mut grid = Grid::new(3, 3);
+        grid[Point::new(1, 2)] = Some(Particle::Sand(Sand));
+
+        grid.run(5);
+
+        assert!(is_sand(&grid, Point::new(1, 2)));
+        assert_eq!(grid.count_filled(), 1);
+    }
+
+    #[test]
+    fn stays_put_when_boxed_in() {
+        let mut grid = Grid::new(3, 2);
+        for x in 0 .. 3 {
+            grid[Point::new(x, 1)] = Some(Particle::Stone(Stone));
+        }
+        grid[Point::new(1, 0)] = Some(Particle::Sand(Sand));
+
+        grid.run(5);
+
+        assert!(is_sand(&grid, Point::new(1, 0)));
+    }
+
+    #[test]
+    fn falls_along_the_walls() {
+        let mut grid = Grid::new(3, 3);
+        grid[Point::new(0, 0)] = Some(Particle::Sand(Sand));
+        grid[Point::new(2, 0)] = Some(Particle::Sand(Sand));
+
+        grid.update();
+
+        assert!(grid[Point::new(0, 1)].is_some());
+        assert!(grid[Point::new(2, 1)].is_some());
+        assert!(grid[Point::new(0, 0)].is_none());
+        assert!(grid[Point::new(2, 0)].is_none());
+    }
+
+    /// Pours `grains` one at a time onto the middle of a wide floor and
+    /// returns the height of the pile and the width of its base.
+    fn pour(material: &dyn Material, grains: usize) -> (usize, usize) {
+        let (width, height) = (96, 48);
+        let mut grid = Grid::with_seed(width, height, 9);
+        for _ in 0 .. grains {
+            grid[Point::new(width as i32 / 2, 0)] = Some(material.particle());
+            grid.run(2);
+        }
+        grid.run_until_settled(10_000).expect("the pile settles");
+
+        let rows: Vec<usize> = (0 .. height)
+            .filter(|&y| (0 .. width).any(|x| grid[Point::new(x as i32, y as i32)].is_some()))
+            .collect();
+        let base = (0 .. width)
+            .filter(|&x| grid[Point::new(x as i32, height as i32 - 1)].is_some())
+            .count();
+
+        (rows.len(), base)
+    }
+
+    #[test]
+    fn piles_steeper_than_a_powder_without_repose() {
+        assert_eq!(Gunpowder.repose(), 0);
+        let (sand_height, sand_base) = pour(&Sand, 300);
+        let (powder_height, powder_base) = pour(&Gunpowder, 300);
+
+        // Without repose a pile is a 45 degree cone, twice as wide as high.
+        let slope = |height: usize, base: usize| 2.0 * height as f32 / base as f32;
+        assert!(slope(powder_height, powder_base) <= 1.1, "{}x{}", powder_height, powder_base);
+        assert!(
+            slope(sand_height, sand_base) > 1.3 * slope(powder_height, powder_base),
+            "sand {}x{}, gunpowder {}x{}", sand_height, sand_base, powder_height, powder_base,
+        );
+    }
+
+    #[test]
+    fn slides_to_either_diagonal() {
+        let mut grid = Grid::new(3, 2);
+        let (mut left, mut right) = (0, 0);
+
+        for _ in 0 .. 200 {
+            grid.erase_circle(Point::new(1, 1), 2);
+            grid[Point::new(1, 0)] = Some(Particle::Sand(Sand));
+            grid[Point::new(1, 1)] = Some(Particle::Sand(Sand));
+
+            grid.update();
+
+            if grid[Point::new(0, 1)].is_some() { left += 1; }
+            if grid[Point::new(2, 1)].is_some() { right += 1; }
+        }
+
+        // The rest held still, as sand on a slope sometimes does.
+        let held = 200 - left - right;
+        assert!(held > 90 && held < 160, "held: {}", held);
+        assert!(left > 15 && right > 15, "left: {}, right: {}", left, right);
+    }
+
+    #[test]
+    fn rolls_down_a_stone_ramp() {
+        // A 45 degree ramp of stone steps falling away to the right, down
+        // to the floor.
+        let (width, height) = (24, 20);
+        let mut grid = Grid::with_seed(width, height, 3);
+        for x in 0 .. 16 {
+            for y in 4 + x .. height as i32 {
+                grid[Point::new(x, y)] = Some(Particle::Stone(Stone));
+            }
+        }
+
+        let grains = 8;
+        for _ in 0 .. grains {
+            grid[Point::new(1, 0)] = Some(Particle::Sand(Sand));
+            grid.run(40);
+        }
+        assert!(grid.run_until_settled(1000).is_some(), "the grains keep rocking");
+
+        // Every grain rolled to the foot of the ramp, each stopping against
+        // the one before, rather than any resting part way down.
+        let lowest = height - grains;
+        let resting: Vec<_> = grid.iter_filled()
+            .filter(|(_, material)| material.id() == Sand.id())
+            .map(|(point, _)| point)
+            .collect();
+        assert_eq!(resting.len(), grains);
+        assert!(resting.iter().all(|point| point.y as usize >= lowest), "{:?}", resting);
+    }
+
+    #[test]
+    fn fuses_into_glass_when_heated() {
+        let mut grid = Grid::new(1, 1);
+        grid[Point::new(0, 0)] = Some(Particle::Sand(Sand));
+        grid.update();
+        assert_eq!(grid[Point::new(0, 0)].as_ref().map(|m| m.id()), Some(Sand.id()));
+
+        grid.set_temperature(Point::new(0, 0), FUSING_POINT + 100.0);
+        grid.update();
+
+        assert_eq!(grid[Point::new(0, 0)].as_ref().map(|m| m.id()), Some(Glass.id()));
+    }
+
+    #[test]
+    fn falls_up_under_reversed_gravity() {
+        let mut grid = Grid::new(1, 3);
+        grid.set_gravity((0, -1));
+        grid[Point::new(0, 2)] = Some(Particle::Sand(Sand));
+
+        grid.update();
+        grid.update();
+
+        assert!(grid[Point::new(0, 0)].is_some());
+        assert!(grid[Point::new(0, 2)].is_none());
+    }
+}
@@ -0,0 +1,144 @@
+use rand::{
+    Rng,
+    RngCore,
+};
+
+use crate::{
+    material,
+    Color,
+    Grid,
+    Material,
+    Particle,
+    Point,
+};
+
+/// How many ticks smoke lingers before dissipating.
+const LIFE: u16 = 120;
+
+/// Share of the brush a stroke of smoke fills, so it comes out in puffs.
+const SPAWN_DENSITY: f32 = 0.5;
+
+/// Rises and drifts until it thins out into nothing.
+#[derive(Clone, Copy)]
+pub struct Smoke {
+    life: u16,
+}
+
+impl Smoke {
+    pub fn new() -> Smoke {
+        Smoke { life: LIFE }
+    }
+}
+
+impl Default for Smoke {
+    fn default() -> Smoke {
+        Smoke::new()
+    }
+}
+
+impl Material for Smoke {
+    fn update(&self, grid: &Grid, position: Point, rng: &mut dyn RngCore) -> Point {
+        let up = material::relative(grid, position, 0, -1);
+        let (left, right) = (
+            material::relative(grid, position, -1, -1),
+            material::relative(grid, position, 1, -1),
+        );
+        let (first, second) = if rng.gen() { (left, right) } else { (right, left) };
+
+        material::first_free(grid, self.density(), &[up, first, second])
+            .unwrap_or(position)
+    }
+
+    fn color(&self) -> Color {
+        Color::rgb(70, 70, 75)
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Smoke(*self)
+    }
+
+    fn id(&self) -> u16 {
+        8
+    }
+
+    fn name(&self) -> &'static str {
+        "smoke"
+    }
+
+    fn description(&self) -> &'static str {
+        "Drifts upward and thins away."
+    }
+
+    fn density(&self) -> f32 {
+        0.05
+    }
+
+    fn spawn_density(&self) -> f32 {
+        SPAWN_DENSITY
+    }
+
+    fn wind_response(&self) -> f32 {
+        0.8
+    }
+
+    fn heat_transfer(&self) -> f32 {
+        0.05
+    }
+
+    fn is_restless(&self) -> bool {
+        true
+    }
+
+    fn life(&mut self) -> Option<&mut u16> {
+        Some(&mut self.life)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Stone;
+
+    #[test]
+    fn rises_and_dissipates() {
+        let mut grid = Grid::new(1, 4);
+        grid[Point::new(0, 3)] = Some(Particle::Smoke(Smoke::new()));
+
+        for _ in 0 .. 3 {
+            grid.update();
+        }
+        assert!(grid[Point::new(0, 0)].is_some());
+
+        for _ in 3 ..= LIFE {
+            grid.update();
+        }
+        assert!(grid[Point::new(0, 0)].is_none());
+    }
+
+    #[test]
+    fn vanishes_once_its_life_runs_out() {
+        let mut smoke = Smoke::new();
+
+        for _ in 0 .. LIFE {
+            assert!(smoke.tick_life().is_none());
+        }
+
+        assert!(matches!(smoke.tick_life(), Some(None)));
+    }
+
+    #[test]
+    fn does_not_pass_through_solids() {
+        let mut grid = Grid::new(3, 3);
+        for x in 0 .. 3 {
+            grid[Point::new(x, 0)] = Some(Particle::Stone(Stone));
+        }
+        grid[Point::new(1, 2)] = Some(Particle::Smoke(Smoke::new()));
+
+        for _ in 0 .. 5 {
+            grid.update();
+        }
+
+        assert!((0 .. 3).all(|x| grid[Point::new(x, 0)].as_ref().is_some_and(|m| m.is_static())));
+        assert_eq!(grid[Point::new(1, 1)].as_ref().map(|m| m.id()), Some(Smoke::new().id()));
+    }
+}
@@ -0,0 +1,139 @@
+use rand::{
+    Rng,
+    RngCore,
+};
+
+use crate::{
+    material,
+    Cell,
+    Color,
+    Grid,
+    Material,
+    Particle,
+    Point,
+    Water,
+};
+
+/// Temperature below which steam condenses, in degrees Celsius. A little
+/// under water's boiling point so a cell near it doesn't flip back and forth.
+const CONDENSATION_POINT: f32 = 90.0;
+
+/// How hot freshly made steam is.
+const TEMPERATURE: f32 = 120.0;
+
+/// Chance per tick that drifting steam condenses regardless of its
+/// temperature, so even a cloud that can't shed its heat rains out.
+const CONDENSE_CHANCE: f64 = 0.002;
+
+/// Water vapour, which rises and turns back into water as it cools.
+#[derive(Clone, Copy)]
+pub struct Steam;
+
+impl Material for Steam {
+    /// Rises like an upside-down liquid, spreading along ceilings.
+    fn update(&self, grid: &Grid, position: Point, _rng: &mut dyn RngCore) -> Point {
+        let candidates = [
+            material::relative(grid, position, 0, -1),
+            material::relative(grid, position, -1, -1),
+            material::relative(grid, position, 1, -1),
+            material::relative(grid, position, -1, 0),
+            material::relative(grid, position, 1, 0),
+        ];
+
+        material::first_free(grid, self.density(), &candidates).unwrap_or(position)
+    }
+
+    fn color(&self) -> Color {
+        Color::rgb(225, 230, 235)
+    }
+
+    fn shade_variation(&self) -> u8 {
+        12
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Steam(*self)
+    }
+
+    fn id(&self) -> u16 {
+        5
+    }
+
+    fn name(&self) -> &'static str {
+        "steam"
+    }
+
+    fn description(&self) -> &'static str {
+        "Rises and condenses back into water."
+    }
+
+    fn density(&self) -> f32 {
+        0.1
+    }
+
+    fn wind_response(&self) -> f32 {
+        0.8
+    }
+
+    fn heat_transfer(&self) -> f32 {
+        0.1
+    }
+
+    fn spawn_temperature(&self) -> Option<f32> {
+        Some(TEMPERATURE)
+    }
+
+    fn phase_change(&self, temperature: f32) -> Option<Particle> {
+        if temperature < CONDENSATION_POINT {
+            Some(Particle::Water(Water))
+        } else {
+            None
+        }
+    }
+
+    fn tick(&mut self, rng: &mut dyn RngCore) -> Option<Cell> {
+        if rng.gen_bool(CONDENSE_CHANCE) {
+            Some(Some(Particle::Water(Water)))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id_at(grid: &Grid, point: Point) -> Option<u16> {
+        grid[point].as_ref().map(|m| m.id())
+    }
+
+    #[test]
+    fn condenses_once_cooled() {
+        let mut grid = Grid::new(1, 1);
+        grid.paint_circle(Point::new(0, 0), 0, &Steam);
+        grid.update();
+        assert_eq!(id_at(&grid, Point::new(0, 0)), Some(Steam.id()));
+
+        grid.set_temperature(Point::new(0, 0), 50.0);
+        grid.update();
+
+        assert_eq!(id_at(&grid, Point::new(0, 0)), Some(Water.id()));
+    }
+
+    #[test]
+    fn rains_back_down_from_the_ceiling() {
+        let mut grid = Grid::new(3, 3);
+        grid.paint_circle(Point::new(1, 2), 0, &Steam);
+
+        let fallen = |grid: &Grid| {
+            (0 .. 3).any(|x| id_at(grid, Point::new(x, 2)) == Some(Water.id()))
+        };
+        for _ in 0 .. 5000 {
+            grid.update();
+            if fallen(&grid) { break; }
+        }
+
+        assert!(fallen(&grid));
+    }
+}
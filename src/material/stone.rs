@@ -0,0 +1,54 @@
+use rand::RngCore;
+
+use crate::{
+    Color,
+    Grid,
+    Material,
+    Particle,
+    Point,
+};
+
+#[derive(Clone, Copy)]
+pub struct Stone;
+
+impl Material for Stone {
+    fn update(&self, _grid: &Grid, position: Point, _rng: &mut dyn RngCore) -> Point {
+        position
+    }
+
+    fn color(&self) -> Color {
+        Color::rgb(120, 120, 120)
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Stone(*self)
+    }
+
+    fn id(&self) -> u16 {
+        3
+    }
+
+    fn name(&self) -> &'static str {
+        "stone"
+    }
+
+    fn description(&self) -> &'static str {
+        "Solid and unmoving."
+    }
+
+    fn shade_variation(&self) -> u8 {
+        8
+    }
+
+    fn density(&self) -> f32 {
+        f32::INFINITY
+    }
+
+    fn acid_resistant(&self) -> bool {
+        true
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+}
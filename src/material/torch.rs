@@ -0,0 +1,87 @@
+use rand::RngCore;
+
+use crate::{
+    Color,
+    Grid,
+    Material,
+    Particle,
+    Point,
+};
+
+/// Degrees a torch adds to its cell each tick.
+const HEAT: f32 = 15.0;
+
+/// A fixed heat source that never burns out, for melting and boiling things
+/// on purpose.
+#[derive(Clone, Copy)]
+pub struct Torch;
+
+impl Material for Torch {
+    fn update(&self, _grid: &Grid, position: Point, _rng: &mut dyn RngCore) -> Point {
+        position
+    }
+
+    fn color(&self) -> Color {
+        Color::rgb(255, 140, 0)
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Torch(*self)
+    }
+
+    fn id(&self) -> u16 {
+        17
+    }
+
+    fn name(&self) -> &'static str {
+        "torch"
+    }
+
+    fn description(&self) -> &'static str {
+        "Fixed heat source that never burns out."
+    }
+
+    fn density(&self) -> f32 {
+        f32::INFINITY
+    }
+
+    /// Passes its heat on readily, rather than hoarding it.
+    fn heat_transfer(&self) -> f32 {
+        1.0
+    }
+
+    fn heat_output(&self) -> f32 {
+        HEAT
+    }
+
+    fn acid_resistant(&self) -> bool {
+        true
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+
+    /// Keeps the torch heating once everything around it settles.
+    fn is_restless(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Ice;
+
+    #[test]
+    fn melts_ice_next_to_it() {
+        let mut grid = Grid::new(2, 1);
+        grid[Point::new(0, 0)] = Some(Particle::Torch(Torch));
+        grid[Point::new(1, 0)] = Some(Particle::Ice(Ice));
+
+        grid.run(100);
+
+        assert_eq!(grid[Point::new(0, 0)].as_ref().map(|m| m.id()), Some(Torch.id()));
+        assert!(grid[Point::new(1, 0)].as_ref().is_none_or(|m| m.id() != Ice.id()));
+    }
+}
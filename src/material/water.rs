@@ -0,0 +1,145 @@
+use rand::RngCore;
+
+use crate::{
+    material,
+    Color,
+    Grid,
+    Material,
+    Particle,
+    Point,
+    Steam,
+};
+
+/// Temperature above which water boils into steam, in degrees Celsius.
+const BOILING_POINT: f32 = 100.0;
+
+/// Share of the brush a stroke of water fills, so it pours in drips.
+const SPAWN_DENSITY: f32 = 0.3;
+
+#[derive(Clone, Copy)]
+pub struct Water;
+
+impl Material for Water {
+    fn update(&self, grid: &Grid, position: Point, rng: &mut dyn RngCore) -> Point {
+        material::flow(grid, position, self, rng)
+    }
+
+    fn color(&self) -> Color {
+        Color::rgb(40, 110, 200)
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Water(*self)
+    }
+
+    fn id(&self) -> u16 {
+        2
+    }
+
+    fn name(&self) -> &'static str {
+        "water"
+    }
+
+    fn description(&self) -> &'static str {
+        "Flows and levels out, boiling into steam when hot."
+    }
+
+    fn density(&self) -> f32 {
+        1.0
+    }
+
+    fn spawn_density(&self) -> f32 {
+        SPAWN_DENSITY
+    }
+
+    fn wind_response(&self) -> f32 {
+        0.5
+    }
+
+    fn finds_level(&self) -> bool {
+        true
+    }
+
+    fn heat_transfer(&self) -> f32 {
+        0.6
+    }
+
+    fn phase_change(&self, temperature: f32) -> Option<Particle> {
+        if temperature > BOILING_POINT {
+            Some(Particle::Steam(Steam))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Stone;
+
+    #[test]
+    fn columns_collapse_into_flat_pools() {
+        let (width, height) = (64, 24);
+        let mut grid = Grid::new(width, height);
+        for y in 2 .. height as i32 {
+            for x in 0 .. 4 {
+                grid[Point::new(x, y)] = Some(Particle::Water(Water));
+            }
+        }
+
+        grid.run(150);
+
+        // 88 cells of water fill just under two rows.
+        let wet_rows = (0 .. height as i32)
+            .filter(|&y| (0 .. width as i32).any(|x| grid[Point::new(x, y)].is_some()))
+            .count();
+        assert_eq!(wet_rows, 2);
+    }
+
+    #[test]
+    fn rises_up_the_other_arm_of_a_u_bend() {
+        // Two one-cell-wide arms joined along the bottom, under a stone
+        // divider, with the left arm filled.
+        let (width, height) = (7, 10);
+        let mut grid = Grid::new(width, height);
+        for y in 0 .. height as i32 {
+            grid[Point::new(0, y)] = Some(Particle::Stone(Stone));
+            grid[Point::new(6, y)] = Some(Particle::Stone(Stone));
+        }
+        for x in 0 .. width as i32 {
+            grid[Point::new(x, 9)] = Some(Particle::Stone(Stone));
+        }
+        for y in 0 .. 8 {
+            for x in 2 .. 5 {
+                grid[Point::new(x, y)] = Some(Particle::Stone(Stone));
+            }
+        }
+        for x in 1 .. 6 {
+            grid[Point::new(x, 8)] = Some(Particle::Water(Water));
+        }
+        for y in 2 .. 8 {
+            grid[Point::new(1, y)] = Some(Particle::Water(Water));
+        }
+
+        grid.run(100);
+
+        // Eleven cells of water: five along the bottom and three up each arm.
+        let is_water = |x, y| grid[Point::new(x, y)].as_ref().is_some_and(|m| m.id() == 2);
+        for y in 5 .. 8 {
+            assert!(is_water(1, y) && is_water(5, y));
+        }
+        assert!(!is_water(1, 4) && !is_water(5, 4));
+    }
+
+    #[test]
+    fn boils_into_steam() {
+        let mut grid = Grid::new(1, 1);
+        grid[Point::new(0, 0)] = Some(Particle::Water(Water));
+
+        grid.set_temperature(Point::new(0, 0), 150.0);
+        grid.update();
+
+        assert_eq!(grid[Point::new(0, 0)].as_ref().map(|m| m.id()), Some(Steam.id()));
+    }
+}
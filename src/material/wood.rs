@@ -0,0 +1,126 @@
+use rand::RngCore;
+
+use crate::{
+    Color,
+    Fire,
+    Grid,
+    Material,
+    Particle,
+    Point,
+};
+
+/// Temperature at which wood bursts into flame without any fire nearby, in
+/// degrees Celsius.
+const IGNITION_POINT: f32 = 300.0;
+
+/// A static solid that burns away flame by flame.
+#[derive(Clone, Copy)]
+pub struct Wood;
+
+impl Material for Wood {
+    fn update(&self, _grid: &Grid, position: Point, _rng: &mut dyn RngCore) -> Point {
+        position
+    }
+
+    fn color(&self) -> Color {
+        Color::rgb(110, 70, 35)
+    }
+
+    fn particle(&self) -> Particle {
+        Particle::Wood(*self)
+    }
+
+    fn id(&self) -> u16 {
+        7
+    }
+
+    fn name(&self) -> &'static str {
+        "wood"
+    }
+
+    fn description(&self) -> &'static str {
+        "Solid and flammable."
+    }
+
+    fn shade_variation(&self) -> u8 {
+        10
+    }
+
+    fn density(&self) -> f32 {
+        f32::INFINITY
+    }
+
+    fn heat_transfer(&self) -> f32 {
+        0.1
+    }
+
+    fn phase_change(&self, temperature: f32) -> Option<Particle> {
+        if temperature >= IGNITION_POINT {
+            Some(Particle::Fire(Fire::new()))
+        } else {
+            None
+        }
+    }
+
+    fn is_static(&self) -> bool {
+        true
+    }
+
+    fn flammable(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Sand,
+        Stone,
+        Water,
+    };
+
+    #[test]
+    fn only_wood_is_flammable() {
+        assert!(Wood.flammable());
+        assert!(!Stone.flammable());
+        assert!(!Sand.flammable());
+        assert!(!Water.flammable());
+    }
+
+    #[test]
+    fn burns_progressively() {
+        let mut grid = Grid::new(20, 1);
+        for x in 1 .. 20 {
+            grid[Point::new(x, 0)] = Some(Particle::Wood(Wood));
+        }
+        grid[Point::new(0, 0)] = Some(Particle::Fire(Fire::new()));
+        let wood_left = |grid: &Grid| {
+            (0 .. 20)
+                .filter(|&x| grid[Point::new(x, 0)].as_ref().map(|m| m.id()) == Some(Wood.id()))
+                .count()
+        };
+
+        for _ in 0 .. 20 {
+            grid.update();
+        }
+        let burning = wood_left(&grid);
+        assert!(burning > 0 && burning < 19, "{} planks left", burning);
+
+        for _ in 0 .. 1000 {
+            grid.update();
+        }
+        assert_eq!(wood_left(&grid), 0);
+    }
+
+    #[test]
+    fn ignites_when_hot_enough() {
+        let mut grid = Grid::new(1, 1);
+        grid[Point::new(0, 0)] = Some(Particle::Wood(Wood));
+
+        grid.set_temperature(Point::new(0, 0), IGNITION_POINT);
+        grid.update();
+
+        assert_eq!(grid[Point::new(0, 0)].as_ref().map(|m| m.id()), Some(Fire::new().id()));
+    }
+}
@@ -0,0 +1,204 @@
+use std::{
+    fmt,
+    str::FromStr,
+};
+
+pub const USAGE: &str = "\
+usage: into_the_breach [OPTIONS] [IMAGE]
+
+  IMAGE                 PNG to seed the grid from, matched against material colors
+
+options:
+  --width <PIXELS>      window width (default 1920)
+  --height <PIXELS>     window height (default 1020)
+  --pixel-size <PIXELS> size of one cell on screen (default 5)
+  --present <MODE>      capped (default), vsync, or uncapped frame pacing
+  --seed <NUMBER>       replay the random choices of an earlier run
+  --palette <FILE>      RON file recoloring materials, e.g. {\"sand\": (255, 200, 80)}
+  --record-every <N>    keep one frame in N when recording with shift+R (default 1)
+  -h, --help            print this message";
+
+const WIDTH: u32 = 1920;
+const HEIGHT: u32 = 1020;
+const PIXEL_SIZE: u32 = 5;
+
+/// How frames are paced.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Present {
+    /// Sleeping off the rest of each frame to hold the target frame rate.
+    Capped,
+    /// Waiting for the display's vertical sync as well, so frames never
+    /// tear.
+    Vsync,
+    /// Drawing frames as fast as they can be made, for the lowest latency.
+    Uncapped,
+}
+
+impl FromStr for Present {
+    type Err = ();
+
+    fn from_str(mode: &str) -> Result<Present, ()> {
+        match mode {
+            "capped" => Ok(Present::Capped),
+            "vsync" => Ok(Present::Vsync),
+            "uncapped" => Ok(Present::Uncapped),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Present {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Present::Capped => "capped",
+            Present::Vsync => "vsync",
+            Present::Uncapped => "uncapped",
+        })
+    }
+}
+
+/// Launch settings taken from the command line, with anything not given left
+/// at its default.
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub width: u32,
+    pub height: u32,
+    pub pixel_size: u32,
+    pub image: Option<String>,
+    pub palette: Option<String>,
+    pub present: Present,
+    /// Seed for the simulation's random choices, picked at random if not
+    /// given.
+    pub seed: Option<u64>,
+    /// How many rendered frames go by for each one a recording keeps.
+    pub record_every: u32,
+    pub help: bool,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            width: WIDTH,
+            height: HEIGHT,
+            pixel_size: PIXEL_SIZE,
+            image: None,
+            palette: None,
+            present: Present::Capped,
+            seed: None,
+            record_every: 1,
+            help: false,
+        }
+    }
+}
+
+impl Options {
+    /// Parses arguments, excluding the program name, and checks that they
+    /// describe a usable grid.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--width" => options.width = value(&arg, args.next())?,
+                "--height" => options.height = value(&arg, args.next())?,
+                "--pixel-size" => options.pixel_size = value(&arg, args.next())?,
+                "--present" => options.present = value(&arg, args.next())?,
+                "--seed" => options.seed = Some(value(&arg, args.next())?),
+                "--palette" => options.palette = Some(value(&arg, args.next())?),
+                "--record-every" => options.record_every = value(&arg, args.next())?,
+                "-h" | "--help" => options.help = true,
+                flag if flag.starts_with('-') => {
+                    return Err(format!("unknown option {}", flag));
+                },
+                _ if options.image.is_none() => options.image = Some(arg),
+                _ => return Err(format!("unexpected argument {}", arg)),
+            }
+        }
+
+        options.validate()?;
+        Ok(options)
+    }
+
+    /// Number of cells across and down the grid.
+    pub fn grid_size(&self) -> (usize, usize) {
+        ((self.width / self.pixel_size) as usize, (self.height / self.pixel_size) as usize)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 || self.pixel_size == 0 {
+            return Err("width, height and pixel size must be positive".into());
+        }
+        // Sizes that don't divide evenly leave a border round the grid.
+        if self.pixel_size > self.width || self.pixel_size > self.height {
+            return Err(format!(
+                "pixel size {} must fit within both the width {} and height {}",
+                self.pixel_size, self.width, self.height,
+            ));
+        }
+        if self.record_every == 0 {
+            return Err("--record-every must be at least 1".into());
+        }
+
+        Ok(())
+    }
+}
+
+fn value<T: FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{} needs a value", flag))?;
+    value.parse()
+        .map_err(|_| format!("invalid value {} for {}", value, flag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn defaults_apply_to_missing_flags() {
+        let options = parse(&["--pixel-size", "6", "level.png", "--palette", "dark.ron"]).unwrap();
+
+        assert_eq!(options, Options {
+            pixel_size: 6,
+            image: Some("level.png".into()),
+            palette: Some("dark.ron".into()),
+            ..Options::default()
+        });
+        assert_eq!(options.grid_size(), (320, 170));
+    }
+
+    #[test]
+    fn parses_present_modes() {
+        assert_eq!(parse(&["--pixel-size", "4", "--present", "vsync"]).unwrap().present, Present::Vsync);
+        assert_eq!(parse(&["--pixel-size", "4", "--present", "uncapped"]).unwrap().present, Present::Uncapped);
+    }
+
+    #[test]
+    fn rounds_the_grid_down_to_whole_cells() {
+        let options = parse(&["--width", "100", "--height", "52", "--pixel-size", "3"]).unwrap();
+        assert_eq!(options.grid_size(), (33, 17));
+    }
+
+    #[test]
+    fn rejects_unusable_sizes() {
+        assert!(parse(&["--width", "0"]).is_err());
+        assert!(parse(&["--width", "-3"]).is_err());
+        assert!(parse(&["--width", "2", "--pixel-size", "3"]).is_err());
+        assert!(parse(&["--height"]).is_err());
+        assert!(parse(&["--depth", "3"]).is_err());
+        assert!(parse(&["--present", "sometimes"]).is_err());
+        assert!(parse(&["--seed", "-1"]).is_err());
+        assert!(parse(&["--record-every", "0"]).is_err());
+    }
+
+    #[test]
+    fn defaults_are_usable() {
+        assert_eq!(parse(&[]).unwrap().grid_size(), (384, 204));
+        assert_eq!(parse(&["--seed", "42"]).unwrap().seed, Some(42));
+        assert_eq!(parse(&["--record-every", "4"]).unwrap().record_every, 4);
+    }
+}
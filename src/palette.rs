@@ -0,0 +1,116 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+};
+
+use ron::error::SpannedError;
+
+use crate::{
+    Color,
+    Material,
+    MaterialRegistry,
+};
+
+/// Colors that replace materials' own [`Material::color`] when the grid is
+/// drawn, so materials can be re-skinned without recompiling. Materials it
+/// doesn't list keep their built-in colors.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Palette {
+    /// Colors by [`Material::id`].
+    colors: HashMap<u16, Color>,
+}
+
+/// Why a palette couldn't be read by [`Palette::from_ron`].
+#[derive(Debug)]
+pub enum PaletteError {
+    /// The text isn't a map from material names to `(r, g, b)` triples.
+    Syntax(SpannedError),
+    /// An entry names a material the registry doesn't have.
+    UnknownMaterial(String),
+}
+
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PaletteError::Syntax(error) => write!(f, "{}", error),
+            PaletteError::UnknownMaterial(name) => write!(f, "unknown material {:?}", name),
+        }
+    }
+}
+
+impl Error for PaletteError {}
+
+impl Palette {
+    /// An empty palette, leaving every material its built-in color.
+    pub fn new() -> Palette {
+        Palette::default()
+    }
+
+    /// Reads a palette from RON text such as `{"sand": (255, 200, 80)}`,
+    /// naming materials as [`MaterialRegistry::builtin`] does.
+    pub fn from_ron(text: &str) -> Result<Palette, PaletteError> {
+        Palette::from_ron_with(text, &MaterialRegistry::builtin())
+    }
+
+    /// Like [`Palette::from_ron`], looking names up in `registry`.
+    pub fn from_ron_with(text: &str, registry: &MaterialRegistry) -> Result<Palette, PaletteError> {
+        let entries: HashMap<String, (u8, u8, u8)> = ron::from_str(text)
+            .map_err(PaletteError::Syntax)?;
+
+        let mut palette = Palette::new();
+        for (name, (r, g, b)) in entries {
+            let material = registry.create(&name).ok_or(PaletteError::UnknownMaterial(name))?;
+            palette.set(&*material, Color::rgb(r, g, b));
+        }
+
+        Ok(palette)
+    }
+
+    /// Draws `material` in `color` from now on.
+    pub fn set(&mut self, material: &dyn Material, color: Color) {
+        self.colors.insert(material.id(), color);
+    }
+
+    /// The color `material` is drawn in, if the palette overrides it.
+    pub fn color(&self, material: &dyn Material) -> Option<Color> {
+        self.colors.get(&material.id()).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Grid,
+        Particle,
+        Point,
+        Sand,
+        Stone,
+    };
+
+    #[test]
+    fn recolors_listed_materials_only() {
+        let mut grid = Grid::new(2, 1);
+        grid[Point::new(0, 0)] = Some(Particle::Sand(Sand));
+        grid[Point::new(1, 0)] = Some(Particle::Stone(Stone));
+        let stone = grid.to_rgb_buffer()[3 ..].to_vec();
+
+        grid.set_palette(Palette::from_ron("{\"sand\": (100, 150, 200)}").unwrap());
+
+        // Sand keeps its per-particle shade, which offsets every channel
+        // equally.
+        let buffer = grid.to_rgb_buffer();
+        let (r, g, b) = (buffer[0] as i16, buffer[1] as i16, buffer[2] as i16);
+        assert!((r - 100).abs() <= Sand.shade_variation() as i16);
+        assert_eq!((g - r, b - r), (50, 100));
+        assert_eq!(buffer[3 ..], stone[..]);
+    }
+
+    #[test]
+    fn rejects_unknown_materials() {
+        let unknown = "{\"unobtainium\": (1, 2, 3)}";
+        assert!(matches!(Palette::from_ron(unknown), Err(PaletteError::UnknownMaterial(_))));
+        assert!(matches!(Palette::from_ron("(1, 2)"), Err(PaletteError::Syntax(_))));
+    }
+}
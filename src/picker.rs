@@ -0,0 +1,147 @@
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+use into_the_breach::{
+    Material,
+    MaterialRegistry,
+    Palette,
+};
+
+/// Width and height of each material's swatch, in pixels.
+const SWATCH: u32 = 24;
+
+/// Space around each swatch.
+const GAP: u32 = 6;
+
+/// Width of the strip the picker takes up beside the playfield.
+pub const WIDTH: u32 = SWATCH + 2 * GAP;
+
+const BACKGROUND: Color = Color::RGB(30, 30, 30);
+
+/// Outline around the swatch of the brush material.
+const HIGHLIGHT: Color = Color::RGB(255, 255, 255);
+
+/// A column of swatches down the side of the window, one per registered
+/// material in the color it is drawn in, for choosing the brush material
+/// by clicking.
+pub struct Picker {
+    /// Names and colors of the registry's materials, in registration order.
+    swatches: Vec<(String, Color)>,
+    /// The strip the picker takes up in the window.
+    area: Rect,
+}
+
+impl Picker {
+    /// A picker for the materials in `registry`, colored as `palette` draws
+    /// them, filling a strip `height` pixels tall with its left edge at
+    /// `left`.
+    pub fn new(registry: &MaterialRegistry, palette: &Palette, left: i32, height: u32) -> Picker {
+        let swatches = registry.names()
+            .filter_map(|name| registry.create(name).map(|material| (name.to_string(), material)))
+            .map(|(name, material)| {
+                let color = palette.color(&*material).unwrap_or_else(|| material.color());
+                (name, Color::RGB(color.r, color.g, color.b))
+            })
+            .collect();
+
+        Picker { swatches, area: Rect::new(left, 0, WIDTH, height) }
+    }
+
+    /// Whether a window position lies on the picker rather than the
+    /// playfield.
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        self.area.contains_point((x, y))
+    }
+
+    /// Name of the material whose swatch is at a window position, if any.
+    pub fn material_at(&self, x: i32, y: i32) -> Option<&str> {
+        self.swatches.iter()
+            .enumerate()
+            .find(|&(index, _)| self.swatch(index).contains_point((x, y)))
+            .map(|(_, (name, _))| name.as_str())
+    }
+
+    /// Draws the strip and its swatches, outlining the one for `selected`.
+    pub fn draw(&self, canvas: &mut Canvas<Window>, selected: &dyn Material) -> Result<(), String> {
+        canvas.set_draw_color(BACKGROUND);
+        canvas.fill_rect(self.area)?;
+
+        for (index, (name, color)) in self.swatches.iter().enumerate() {
+            let swatch = self.swatch(index);
+            if name == selected.name() {
+                let border = GAP as i32 / 2;
+                canvas.set_draw_color(HIGHLIGHT);
+                canvas.draw_rect(Rect::new(
+                    swatch.x() - border,
+                    swatch.y() - border,
+                    SWATCH + 2 * border as u32,
+                    SWATCH + 2 * border as u32,
+                ))?;
+            }
+            canvas.set_draw_color(*color);
+            canvas.fill_rect(swatch)?;
+        }
+
+        Ok(())
+    }
+
+    /// Where the `index`th swatch is drawn.
+    fn swatch(&self, index: usize) -> Rect {
+        Rect::new(
+            self.area.x() + GAP as i32,
+            self.area.y() + (GAP + index as u32 * (SWATCH + GAP)) as i32,
+            SWATCH,
+            SWATCH,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use into_the_breach::{
+        Color as CellColor,
+        Sand,
+        Water,
+    };
+
+    fn picker() -> Picker {
+        Picker::new(&MaterialRegistry::builtin(), &Palette::new(), 100, 600)
+    }
+
+    #[test]
+    fn lists_every_registered_material() {
+        let registry = MaterialRegistry::builtin();
+        let names: Vec<_> = picker().swatches.into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, registry.names().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn tells_swatches_from_the_playfield() {
+        let picker = picker();
+        let first = GAP as i32;
+
+        assert!(!picker.contains(99, first));
+        assert!(picker.contains(100, first));
+        assert_eq!(picker.material_at(100 + first, first), Some("sand"));
+        let second = first + (SWATCH + GAP) as i32;
+        assert_eq!(picker.material_at(100 + first, second), Some("water"));
+        // Between swatches.
+        assert_eq!(picker.material_at(100 + first, second - 1), None);
+    }
+
+    #[test]
+    fn colors_swatches_from_the_palette() {
+        let mut palette = Palette::new();
+        palette.set(&Water, CellColor::rgb(200, 0, 100));
+        let picker = Picker::new(&MaterialRegistry::builtin(), &palette, 100, 600);
+
+        let color = |name| picker.swatches.iter().find(|(swatch, _)| swatch == name).unwrap().1;
+        assert_eq!(color("water"), Color::RGB(200, 0, 100));
+        let sand = Sand.color();
+        assert_eq!(color("sand"), Color::RGB(sand.r, sand.g, sand.b));
+    }
+}
@@ -0,0 +1,129 @@
+use std::{
+    fs,
+    path::Path,
+};
+
+use image::{
+    ColorType,
+    ImageResult,
+};
+
+use into_the_breach::Grid;
+
+/// Frames of the grid kept while recording, at one pixel per cell, to be
+/// written out as a numbered PNG sequence when recording stops.
+pub struct Recording {
+    /// Each kept frame as [`Grid::to_rgb_buffer`] gave it.
+    frames: Vec<Vec<u8>>,
+    width: u32,
+    height: u32,
+    /// Only every `every`th frame offered is kept.
+    every: u32,
+    /// Frames offered so far, kept or not.
+    offered: u32,
+}
+
+impl Recording {
+    /// A recording that keeps one frame in `every`, so long recordings stay
+    /// small. `every` is at least 1.
+    pub fn new(every: u32) -> Recording {
+        Recording { frames: Vec::new(), width: 0, height: 0, every: every.max(1), offered: 0 }
+    }
+
+    /// Offers the grid as it is now as the next frame. Frames are all the
+    /// size of the first one kept, so any taken after the grid has been
+    /// resized are dropped.
+    pub fn capture(&mut self, grid: &Grid) {
+        let offered = self.offered;
+        self.offered += 1;
+        if !offered.is_multiple_of(self.every) { return; }
+
+        let (width, height) = (grid.width() as u32, grid.height() as u32);
+        if self.frames.is_empty() {
+            self.width = width;
+            self.height = height;
+        } else if (width, height) != (self.width, self.height) {
+            return;
+        }
+        self.frames.push(grid.to_rgb_buffer());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Writes the frames into `dir`, creating it if need be, as
+    /// `frame-00000.png` onwards, returning how many were written. The
+    /// recording is used up, so its frames are freed however saving goes.
+    pub fn save(self, dir: &Path) -> ImageResult<usize> {
+        fs::create_dir_all(dir)?;
+        for (number, frame) in self.frames.iter().enumerate() {
+            let path = dir.join(format!("frame-{:05}.png", number));
+            image::save_buffer(path, frame, self.width, self.height, ColorType::Rgb8)?;
+        }
+
+        Ok(self.frames.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use into_the_breach::{
+        Particle,
+        Point,
+        Stone,
+    };
+
+    use super::*;
+
+    #[test]
+    fn keeps_one_frame_in_every() {
+        let grid = Grid::new(4, 4);
+        let mut recording = Recording::new(3);
+
+        for _ in 0 .. 10 {
+            recording.capture(&grid);
+        }
+
+        // Frames 0, 3, 6 and 9.
+        assert_eq!(recording.frames.len(), 4);
+    }
+
+    #[test]
+    fn drops_frames_of_a_different_size() {
+        let mut grid = Grid::new(4, 4);
+        let mut recording = Recording::new(1);
+
+        recording.capture(&grid);
+        grid.resize(8, 8).unwrap();
+        recording.capture(&grid);
+
+        assert_eq!(recording.frames.len(), 1);
+    }
+
+    #[test]
+    fn separate_recordings_are_saved_separately() {
+        let root = env::temp_dir().join(format!("into_the_breach-recording-{}", std::process::id()));
+        let mut grid = Grid::new(3, 2);
+
+        let mut first = Recording::new(1);
+        first.capture(&grid);
+        first.capture(&grid);
+        assert_eq!(first.save(&root.join("first")).unwrap(), 2);
+
+        grid[Point::new(0, 0)] = Some(Particle::Stone(Stone));
+        let mut second = Recording::new(1);
+        second.capture(&grid);
+        assert_eq!(second.save(&root.join("second")).unwrap(), 1);
+
+        let saved = |dir: &str| fs::read_dir(root.join(dir)).unwrap().count();
+        assert_eq!((saved("first"), saved("second")), (2, 1));
+        let frame = image::open(root.join("second/frame-00000.png")).unwrap().to_rgb8();
+        assert_eq!(frame.dimensions(), (3, 2));
+        assert_eq!(frame.into_raw(), grid.to_rgb_buffer());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
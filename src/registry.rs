@@ -0,0 +1,178 @@
+use crate::{
+    Acid,
+    Crystal,
+    Drain,
+    Fire,
+    Glass,
+    Gunpowder,
+    Ice,
+    Lava,
+    Life,
+    Material,
+    Metal,
+    Mud,
+    Oil,
+    Particle,
+    Plant,
+    Sand,
+    Smoke,
+    Steam,
+    Stone,
+    Torch,
+    Water,
+    Wood,
+};
+
+type Constructor = Box<dyn Fn() -> Particle>;
+
+/// Materials looked up by name, so callers can create them without knowing
+/// their concrete types. Names are kept in the order they were registered.
+#[derive(Default)]
+pub struct MaterialRegistry {
+    entries: Vec<(String, Constructor)>,
+}
+
+impl MaterialRegistry {
+    pub fn new() -> MaterialRegistry {
+        MaterialRegistry::default()
+    }
+
+    /// A registry holding every material in the crate, each under its
+    /// [`Material::name`].
+    pub fn builtin() -> MaterialRegistry {
+        let constructors: [fn() -> Particle; 20] = [
+            || Particle::Sand(Sand),
+            || Particle::Water(Water),
+            || Particle::Stone(Stone),
+            || Particle::Lava(Lava),
+            || Particle::Steam(Steam),
+            || Particle::Fire(Fire::new()),
+            || Particle::Wood(Wood),
+            || Particle::Smoke(Smoke::new()),
+            || Particle::Ice(Ice),
+            || Particle::Acid(Acid),
+            || Particle::Oil(Oil),
+            || Particle::Glass(Glass),
+            || Particle::Plant(Plant),
+            || Particle::Gunpowder(Gunpowder),
+            || Particle::Metal(Metal),
+            || Particle::Torch(Torch),
+            || Particle::Drain(Drain),
+            || Particle::Crystal(Crystal),
+            || Particle::Mud(Mud::new()),
+            || Particle::Life(Life),
+        ];
+
+        let mut registry = MaterialRegistry::new();
+        for constructor in constructors {
+            registry.register(constructor().name(), constructor);
+        }
+
+        registry
+    }
+
+    /// Adds a material under `name`, replacing any already registered there.
+    pub fn register<F>(&mut self, name: &str, constructor: F)
+    where
+        F: Fn() -> Particle + 'static,
+    {
+        match self.entries.iter_mut().find(|(existing, _)| existing == name) {
+            Some((_, existing)) => *existing = Box::new(constructor),
+            None => self.entries.push((name.to_string(), Box::new(constructor))),
+        }
+    }
+
+    /// A new particle of the material registered as `name`.
+    pub fn create(&self, name: &str) -> Option<Particle> {
+        self.entries.iter()
+            .find(|(existing, _)| existing == name)
+            .map(|(_, constructor)| constructor())
+    }
+
+    /// The name `material` is registered under, matched by
+    /// [`Material::id`].
+    pub fn name_of(&self, material: &dyn Material) -> Option<&str> {
+        self.entries.iter()
+            .find(|(_, constructor)| constructor().id() == material.id())
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// A new particle of the material `steps` places after `material` in
+    /// registration order, wrapping around at either end, with negative
+    /// steps going backwards. Materials the registry doesn't know count as
+    /// sitting just before the first. `None` only if the registry is empty.
+    pub fn cycle(&self, material: &dyn Material, steps: i32) -> Option<Particle> {
+        if self.entries.is_empty() { return None; }
+
+        let len = self.entries.len() as i32;
+        let current = self.entries.iter()
+            .position(|(_, constructor)| constructor().id() == material.id())
+            .map_or(-1, |position| position as i32);
+        let next = (current + steps).rem_euclid(len) as usize;
+
+        Some((self.entries[next].1)())
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(name, _)| name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material;
+
+    #[test]
+    fn creates_materials_by_name() {
+        let registry = MaterialRegistry::builtin();
+
+        assert_eq!(registry.create("sand").unwrap().color(), Sand.color());
+        assert!(registry.create("unobtainium").is_none());
+        assert_eq!(registry.name_of(&Water), Some("water"));
+    }
+
+    #[test]
+    fn registering_a_name_again_replaces_it() {
+        let mut registry = MaterialRegistry::new();
+        registry.register("rock", || Particle::Sand(Sand));
+        registry.register("rock", || Particle::Stone(Stone));
+
+        assert_eq!(registry.names().collect::<Vec<_>>(), ["rock"]);
+        assert_eq!(registry.create("rock").unwrap().id(), Stone.id());
+    }
+
+    #[test]
+    fn builtins_are_registered_under_their_own_names() {
+        let registry = MaterialRegistry::builtin();
+
+        for name in registry.names() {
+            assert_eq!(registry.create(name).unwrap().name(), name);
+        }
+        assert_eq!(registry.names().count(), 20);
+    }
+
+    #[test]
+    fn cycling_wraps_around() {
+        let mut registry = MaterialRegistry::new();
+        registry.register("sand", || Particle::Sand(Sand));
+        registry.register("water", || Particle::Water(Water));
+        registry.register("stone", || Particle::Stone(Stone));
+
+        assert_eq!(registry.cycle(&Sand, 1).unwrap().id(), Water.id());
+        assert_eq!(registry.cycle(&Stone, 1).unwrap().id(), Sand.id());
+        assert_eq!(registry.cycle(&Sand, -1).unwrap().id(), Stone.id());
+        assert_eq!(registry.cycle(&material::Oil, 1).unwrap().id(), Sand.id());
+        assert!(MaterialRegistry::new().cycle(&Sand, 1).is_none());
+    }
+
+    #[test]
+    fn builtins_can_be_loaded_from_saves() {
+        let registry = MaterialRegistry::builtin();
+
+        for name in registry.names() {
+            let id = registry.create(name).unwrap().id();
+            assert!(material::from_id(id).is_some(), "{} can't be loaded", name);
+        }
+    }
+}
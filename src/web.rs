@@ -0,0 +1,97 @@
+use wasm_bindgen::{
+    prelude::*,
+    Clamped,
+    JsCast,
+};
+use web_sys::{
+    CanvasRenderingContext2d,
+    HtmlCanvasElement,
+    ImageData,
+};
+
+use crate::{
+    BrushShape,
+    Grid,
+    MaterialRegistry,
+    Particle,
+    Point,
+    Sand,
+};
+
+const BRUSH_RADIUS: i32 = 3;
+
+/// The simulation running in a browser, drawn at one pixel per cell onto an
+/// HTML canvas. Scale the canvas up with CSS to make cells bigger.
+#[wasm_bindgen]
+pub struct WebApp {
+    grid: Grid,
+    registry: MaterialRegistry,
+    material: Particle,
+    context: CanvasRenderingContext2d,
+    /// Reused between frames for the canvas's RGBA pixels.
+    pixels: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl WebApp {
+    /// Resizes `canvas` to one pixel per cell of a `width` by `height` grid
+    /// and draws to it.
+    #[wasm_bindgen(constructor)]
+    pub fn new(canvas: HtmlCanvasElement, width: usize, height: usize) -> Result<WebApp, JsValue> {
+        canvas.set_width(width as u32);
+        canvas.set_height(height as u32);
+        let context = canvas.get_context("2d")?
+            .ok_or("canvas has no 2d context")?
+            .dyn_into::<CanvasRenderingContext2d>()?;
+
+        Ok(WebApp {
+            grid: Grid::try_new(width, height).map_err(|error| error.to_string())?,
+            registry: MaterialRegistry::builtin(),
+            material: Particle::Sand(Sand),
+            context,
+            pixels: Vec::new(),
+        })
+    }
+
+    /// Advances the simulation one tick.
+    pub fn tick(&mut self) {
+        self.grid.update();
+    }
+
+    /// Switches the brush to the material registered as `name`. Returns
+    /// whether there is one.
+    pub fn select(&mut self, name: &str) -> bool {
+        match self.registry.create(name) {
+            Some(material) => {
+                self.material = material;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Paints, or erases, along a stroke between two cells.
+    pub fn stroke(&mut self, from_x: i32, from_y: i32, to_x: i32, to_y: i32, erase: bool) {
+        let (from, to) = (Point::new(from_x, from_y), Point::new(to_x, to_y));
+        if erase {
+            self.grid.erase_line(from, to, BRUSH_RADIUS);
+        } else {
+            self.grid.paint_line(from, to, BRUSH_RADIUS, BrushShape::Circle, &*self.material);
+        }
+    }
+
+    pub fn draw(&mut self) -> Result<(), JsValue> {
+        self.pixels.clear();
+        for rgb in self.grid.to_rgb_buffer().chunks(3) {
+            self.pixels.extend_from_slice(rgb);
+            self.pixels.push(u8::MAX);
+        }
+
+        let image = ImageData::new_with_u8_clamped_array_and_sh(
+            Clamped(&self.pixels),
+            self.grid.width() as u32,
+            self.grid.height() as u32,
+        )?;
+        self.context.put_image_data(&image, 0.0, 0.0)
+    }
+}